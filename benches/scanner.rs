@@ -0,0 +1,61 @@
+// Copyright 2024 LangVM Project
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+#![allow(nonstandard_style)]
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+use cee::ast::TokenKind;
+use cee::parser::Parser;
+use cee::scanner::*;
+
+// Generated source of roughly `size` bytes, mixing identifiers, literals, comments and non-ASCII text.
+fn Generate(size: usize) -> String {
+    let mut buffer = String::with_capacity(size + 256);
+    let mut i = 0;
+    while buffer.len() < size {
+        buffer.push_str(&format!(
+            "/// Computes value_{i}\nfunc value_{i}(a, b int) <- int {{\n\tlet s = \"héllo {{a}} 世界\" // note\n\treturn a * 0x{i:x}u64 + b / 1.5e3 - '啊'\n}}\n\n",
+        ));
+        i += 1;
+    }
+    buffer
+}
+
+fn BenchScanner(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scanner");
+    for size in [1 << 20, 8 << 20] {
+        let buffer = Generate(size);
+        group.throughput(Throughput::Bytes(buffer.len() as u64));
+
+        let NewScanner = || BasicScanner::new(&buffer, vec!['(', ')', '[', ']', '{', '}', ',', ';', '\n'], vec![' ', '\t', '\r'], TokenKind::Operators());
+
+        // time the token path, not error recovery
+        let mut s = NewScanner();
+        while s.Scan().is_ok() {}
+        assert!(s.Errors.is_empty(), "{:?}", s.Errors);
+
+        group.bench_function(format!("Scan/{}MiB", size >> 20), |b| b.iter(|| {
+            let mut s = NewScanner();
+            let mut count = 0;
+            while s.Scan().is_ok() {
+                count += 1;
+            }
+            count
+        }));
+
+        group.bench_function(format!("Parser.Scan/{}MiB", size >> 20), |b| b.iter(|| {
+            let mut p = Parser::new(&buffer);
+            let mut count = 0;
+            while p.Scan().is_ok() {
+                count += 1;
+            }
+            count
+        }));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, BenchScanner);
+criterion_main!(benches);
@@ -28,6 +28,14 @@ macro_rules! def_ast {
                 write!(f, $fmt, $(self.$e,)*)
             }
         }
+        impl CstSpans for $ast {
+            fn Spans(&self, spans: &mut Vec<(&'static str, PosRange)>) {
+                spans.push((stringify!($ast), self.Pos));
+                $(
+                self.$name.Spans(spans);
+                )*
+            }
+        }
         )*
     };
 }
@@ -62,6 +70,16 @@ macro_rules! def_node {
                 }
             }
         }
+        impl CstSpans for $node {
+            fn Spans(&self, spans: &mut Vec<(&'static str, PosRange)>) {
+                match self {
+                    $node::None => {}
+                    $(
+                    $node::$typ(e) => { e.Spans(spans) }
+                    )*
+                }
+            }
+        }
         )*
     };
 }
@@ -76,8 +94,15 @@ pub struct List<T> {
 
 impl<T> fmt::Display for List<T> where T: fmt::Display {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for e in &self.Elements {
-            write!(f, "{}{}", e, self.Delimiter)?;
+        for (i, e) in self.Elements.iter().enumerate() {
+            // `a, b` and `a; b`, but `A + B` and `A | B`
+            if i != 0 {
+                match self.Delimiter {
+                    TokenKind::COMMA | TokenKind::SEMICOLON => { write!(f, "{} ", self.Delimiter)?; }
+                    _ => { write!(f, " {} ", self.Delimiter)?; }
+                }
+            }
+            write!(f, "{}", e)?;
         }
         Ok(())
     }
@@ -128,14 +153,36 @@ impl<T> fmt::Display for Optional<T> where T: fmt::Display {
 def_ast! {
     Ident ("{}", Token) {
         Token: Token,
+    },
+
+    // Consecutive `///` or `/** */` comments documenting the node that follows them.
+    Doc ("{}", Text) {
+        Comments: Vec<Token>,
+        Text: String,
     }
 }
 
 def_node! {
     Expr {
+        Ident,
         LiteralValue,
+        InterpolationExpr,
+        ParenExpr,
+        UnaryExpr,
+        BinaryExpr,
         CallExpr,
+        SelectorExpr,
+        TupleIndexExpr,
+        IndexExpr,
         UnwrapExpr,
+        IfExpr,
+        MatchExpr,
+        StmtBlock,
+        GenericExpr,
+        BorrowExpr,
+        ArrayExpr,
+        TupleExpr,
+        StructLit,
     }
 }
 
@@ -144,13 +191,296 @@ def_ast! {
         Token: Token,
     },
 
-    CallExpr ("fun ({}) {}", Callee, Params) {
+    ParenExpr ("({})", Expr) {
+        Expr: Expr,
+    },
+
+    // Prefix `-x`, `!x` or `~x`
+    UnaryExpr ("{}{}", Op, Expr) {
+        Op: TokenKind,
+        Expr: Expr,
+    },
+
+    BinaryExpr ("({} {} {})", X, Op, Y) {
+        X: Expr,
+        Op: TokenKind,
+        Y: Expr,
+    },
+
+    CallExpr ("{}({})", Callee, Params) {
         Callee: Expr,
         Params: List<Expr>,
     },
 
+    // Field access `x.Sel`
+    SelectorExpr ("{}.{}", Expr, Sel) {
+        Expr: Expr,
+        Sel: Ident,
+    },
+
+    // `t.0`, a tuple element by its position.
+    TupleIndexExpr ("{}.{}", Expr, Index) {
+        Expr: Expr,
+        Index: Token,
+    },
+
+    IndexExpr ("{}[{}]", Expr, Index) {
+        Expr: Expr,
+        Index: Expr,
+    },
+
+    // Explicit type arguments, `Max<int>(a, b)`.
+    GenericExpr ("{}<{}>", Expr, Args) {
+        Expr: Expr,
+        Args: List<Type>,
+    },
+
+    // `[1, 2, 3]`
+    ArrayExpr ("[{}]", Elements) {
+        Elements: List<Expr>,
+    },
+
+
+    // `X: 1` in a struct literal, or `X` alone taking the variable X.
+    FieldValue ("{}: {}", Name, Value) {
+        Name: Ident,
+        Value: Expr,
+    },
+
     UnwrapExpr("{}?", Expr) {
         Expr: Expr,
+    },
+
+    // Also a statement; the value is that of the branch taken.
+    IfExpr ("if {} {}{}", Cond, Then, Else) {
+        Cond: Expr,
+        Then: StmtBlock,
+        Else: Optional<ElseClause>,
+    },
+
+    // Body is a StmtBlock, or an IfExpr for `else if`.
+    ElseClause (" else {}", Body) {
+        Body: Stmt,
+    },
+
+    // The first arm whose pattern matches and whose guard holds is taken.
+    MatchExpr ("match {} {{{}}}", Subject, Arms) {
+        Subject: Expr,
+        Arms: List<MatchArm>,
+    },
+
+    MatchArm ("{}{} => {}", Pattern, Guard, Body) {
+        Pattern: Pattern,
+        Guard: Optional<Guard>,
+        Body: Stmt,
+    },
+
+    Guard (" if {}", Cond) {
+        Cond: Expr,
+    }
+}
+
+impl StmtBlock {
+    // The expression giving the block its value, if any.
+    pub fn Value(&self) -> Option<&Expr> {
+        match self.StmtList.Elements.last() {
+            Some(Stmt::Expr(e)) => { Some(e) }
+            _ => { None }
+        }
+    }
+}
+
+// `self`, `&self` or `&mut self`.
+#[derive(Default)]
+pub struct Receiver {
+    pub Pos: PosRange,
+    pub Borrow: bool,
+    pub Mutable: bool,
+}
+
+impl fmt::Display for Receiver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.Borrow, self.Mutable) {
+            (true, true) => { write!(f, "&mut self") }
+            (true, false) => { write!(f, "&self") }
+            _ => { write!(f, "self") }
+        }
+    }
+}
+
+// `&T` or `&mut T`.
+#[derive(Default)]
+pub struct RefType {
+    pub Pos: PosRange,
+    pub Mutable: bool,
+    pub Elem: Type,
+}
+
+impl fmt::Display for RefType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "&{}{}", if self.Mutable { "mut " } else { "" }, self.Elem)
+    }
+}
+
+// String segments as LiteralValue alternating with the interpolated expressions, in source order.
+#[derive(Default)]
+pub struct InterpolationExpr {
+    pub Pos: PosRange,
+    pub Parts: List<Expr>,
+}
+
+impl fmt::Display for InterpolationExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for (i, part) in self.Parts.Elements.iter().enumerate() {
+            match i % 2 {
+                0 => { write!(f, "{}", part)?; }
+                _ => { write!(f, "{{{}}}", part)?; }
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+// `(a, b)`, `(a,)` with one element, or `()`.
+#[derive(Default)]
+pub struct TupleExpr {
+    pub Pos: PosRange,
+    pub Elements: List<Expr>,
+}
+
+impl fmt::Display for TupleExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.Elements.Elements.len() {
+            1 => { write!(f, "({},)", self.Elements) }
+            _ => { write!(f, "({})", self.Elements) }
+        }
+    }
+}
+
+// `&x` or `&mut x`.
+#[derive(Default)]
+pub struct BorrowExpr {
+    pub Pos: PosRange,
+    pub Mutable: bool,
+    pub Expr: Expr,
+}
+
+impl fmt::Display for BorrowExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "&{}{}", if self.Mutable { "mut " } else { "" }, self.Expr)
+    }
+}
+
+// `Point{X: 1, Y}`, or `Point{X: 1, ..p}` taking the other fields from Base.
+#[derive(Default)]
+pub struct StructLit {
+    pub Pos: PosRange,
+    pub Type: Expr,
+    pub Fields: List<FieldValue>,
+    // None without `..`
+    pub Base: Expr,
+}
+
+impl fmt::Display for StructLit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{{{}", self.Type, self.Fields)?;
+        match (&self.Base, self.Fields.Elements.is_empty()) {
+            (Expr::None, _) => {}
+            (base, true) => { write!(f, "..{}", base)?; }
+            (base, false) => { write!(f, ", ..{}", base)?; }
+        }
+        write!(f, "}}")
+    }
+}
+
+// `Shape::Circle`, segments separated by `::`.
+#[derive(Default)]
+pub struct Path {
+    pub Pos: PosRange,
+    pub Segments: Vec<Ident>,
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, e) in self.Segments.iter().enumerate() {
+            if i != 0 {
+                write!(f, "::")?;
+            }
+            write!(f, "{}", e)?;
+        }
+        Ok(())
+    }
+}
+
+def_node! {
+    Pattern {
+        WildcardPattern,
+        LiteralPattern,
+        BindingPattern,
+        RangePattern,
+        TuplePattern,
+        UnionPattern,
+        StructPattern,
+        OrPattern,
+    }
+}
+
+def_ast! {
+    WildcardPattern ("{}", Token) {
+        Token: Token,
+    },
+
+    // A literal, possibly negated, e.g. `-1`.
+    LiteralPattern ("{}", Value) {
+        Value: Expr,
+    },
+
+    // Binds the matched value to Name. A bare unit variant such as `None` parses as a binding too.
+    BindingPattern ("{}", Name) {
+        Name: Ident,
+    },
+
+    // Values from Low up to High, excluded like the range expression `Low..High`.
+    RangePattern ("{}..{}", Low, High) {
+        Low: LiteralPattern,
+        High: LiteralPattern,
+    },
+
+    TuplePattern ("({})", Elements) {
+        Elements: List<Pattern>,
+    },
+
+    // `Point { X, Y: 0 }`; a field without a pattern binds a variable of the same name.
+    StructPattern ("{} {{{}}}", Type, Fields) {
+        Type: Path,
+        Fields: List<FieldPattern>,
+    },
+
+    FieldPattern ("{}: {}", Name, Pattern) {
+        Name: Ident,
+        Pattern: Pattern,
+    },
+
+    OrPattern ("{}", Alternatives) {
+        Alternatives: List<Pattern>,
+    }
+}
+
+// Union variant with its payload, e.g. `Shape::Circle(r)`, or `Option::None` without one.
+#[derive(Default)]
+pub struct UnionPattern {
+    pub Pos: PosRange,
+    pub Variant: Path,
+    pub Elements: List<Pattern>,
+}
+
+impl fmt::Display for UnionPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.Elements.Elements.is_empty() {
+            true => { write!(f, "{}", self.Variant) }
+            false => { write!(f, "{}({})", self.Variant, self.Elements) }
+        }
     }
 }
 
@@ -160,37 +490,145 @@ def_node! {
         FuncType,
         StructType,
         TraitType,
+        UnionType,
+        GenericType,
+        ArrayType,
+        SliceType,
+        TupleType,
+        OptionalType,
+        RefType,
     }
 }
 
-def_ast! {
-    FuncType ("fun ({}) {}", Params, Result) {
-        Params: List<Field>,
-        Result: Type,
-    },
+// Receiver is the `self` parameter of a method, None for a function.
+#[derive(Default)]
+pub struct FuncType {
+    pub Pos: PosRange,
+    pub Receiver: Optional<Receiver>,
+    pub Params: List<Field>,
+    pub Result: Type,
+}
 
+impl fmt::Display for FuncType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sep = match (&self.Receiver, self.Params.Elements.is_empty()) {
+            (Optional::Some(_), false) => { ", " }
+            _ => { "" }
+        };
+        write!(f, "fun ({}{}{}) {}", self.Receiver, sep, self.Params, self.Result)
+    }
+}
+
+def_ast! {
     StructType ("struct {} {{{}}}", Name, FieldList) {
+        Doc: Optional<Doc>,
         Name: Ident,
+        TypeParams: List<TypeParam>,
+        Where: List<TypeParam>,
         FieldList: List<Field>,
     },
 
     TraitType ("trait {}", Name) {
+        Doc: Optional<Doc>,
         Name: Ident,
+        TypeParams: List<TypeParam>,
+        Where: List<TypeParam>,
         FuncList: List<FuncDecl>,
+    },
+
+    // `Map<K, List<V>>`, a generic type applied to type arguments.
+    GenericType ("{}<{}>", Base, Args) {
+        Base: Ident,
+        Args: List<Type>,
+    },
+
+    // `[4]u8`, Len elements in place.
+    ArrayType ("[{}]{}", Len, Elem) {
+        Len: Expr,
+        Elem: Type,
+    },
+
+    // `[]int`, a view of elements owned elsewhere.
+    SliceType ("[]{}", Elem) {
+        Elem: Type,
+    },
+
+    // `(int, string)`, or `()` with no elements.
+    TupleType ("({})", Elements) {
+        Elements: List<Type>,
+    },
+
+    // `?T`, a T or nothing.
+    OptionalType ("?{}", Elem) {
+        Elem: Type,
+    },
+
+    // `T: Ord + Copy`, a type parameter or a where-clause predicate; Bounds are traits T implements.
+    TypeParam ("{}: {}", Name, Bounds) {
+        Name: Ident,
+        Bounds: List<Type>,
+    },
+
+    // Tagged union, holding exactly one of its variants.
+    UnionType ("union {} {{{}}}", Name, VariantList) {
+        Doc: Optional<Doc>,
+        Name: Ident,
+        TypeParams: List<TypeParam>,
+        Where: List<TypeParam>,
+        VariantList: List<UnionVariant>,
+    },
+
+    // `Circle(R float)`, or `Empty` without payload.
+    UnionVariant ("{}({})", Name, FieldList) {
+        Doc: Optional<Doc>,
+        Name: Ident,
+        FieldList: List<Field>,
     }
 }
 
 def_node! {
     Stmt {
-        MutDecl,
+        VarDecl,
         FuncDecl,
         StmtBlock,
+        LabeledStmt,
+        ForStmt,
+        ForInStmt,
+        BranchStmt,
+        ReturnStmt,
+        AssignStmt,
         Expr,
     }
 }
 
+// Root of a parsed file.
+def_ast! {
+    File ("{}; {}; {}", Package, Imports, Decls) {
+        Package: Optional<PackageClause>,
+        Imports: List<ImportDecl>,
+        Decls: List<Decl>,
+    },
+
+    PackageClause ("package {}", Name) {
+        Doc: Optional<Doc>,
+        Name: Ident,
+    }
+}
+
+def_node! {
+    Decl {
+        StructType,
+        TraitType,
+        UnionType,
+        FuncDecl,
+        ConstDecl,
+        ImplDecl,
+    }
+}
+
 def_ast! {
     Field ("{} {}", Names, Type) {
+        Doc: Optional<Doc>,
         Names: List<Ident>,
         Type: Type,
     },
@@ -200,19 +638,84 @@ def_ast! {
         Canonical: Token,
     },
 
+    // `impl Reader for File { ... }`, the methods of Trait implemented for Type.
+    ImplDecl ("impl {} for {} {{{}}}", Trait, Type, FuncList) {
+        Doc: Optional<Doc>,
+        TypeParams: List<TypeParam>,
+        Where: List<TypeParam>,
+        Trait: Type,
+        Type: Type,
+        FuncList: List<FuncDecl>,
+    },
+
+    // Value is evaluated at compile time; Type is None when inferred from it.
+    ConstDecl ("const {} {} = {}", Name, Type, Value) {
+        Doc: Optional<Doc>,
+        Name: Ident,
+        Type: Type,
+        Value: Expr,
+    },
+
     FuncDecl ("fun {} {} {}", Name, Type, Stmt) {
+        Doc: Optional<Doc>,
         Name: Optional<Ident>,
+        TypeParams: List<TypeParam>,
+        Where: List<TypeParam>,
         Type: FuncType,
         Stmt: Optional<StmtBlock>,
     },
 
-    MutDecl ("mut {}: {}", Name, Type) {
-        Name: Ident,
+    // `let` binds immutably and `mut` or `let mut` mutably, e.g. `let (a, b) = pair` or `mut n int`.
+    // Type is None when omitted, left for the checker to infer from Value.
+    VarDecl ("{} {} {} = {}", Keyword, Pattern, Type, Value) {
+        Keyword: TokenKind,
+        // Whether the bindings may be assigned to after initialization
+        Mutable: bool,
+        Pattern: Pattern,
         Type: Type,
+        Value: Expr,
     },
 
+    // Also an expression, valued by its last statement when that is an expression.
     StmtBlock("{{{}}}", StmtList) {
         StmtList: List<Stmt>,
+        // Type of the block value, filled in by the checker
         Type: Type,
+    },
+
+    // `outer: for { ... }`, the target of a labeled break or continue.
+    LabeledStmt ("{}: {}", Label, Stmt) {
+        Label: Ident,
+        Stmt: Stmt,
+    },
+
+    // `for cond { ... }`, or `for { ... }` looping forever when Cond is None.
+    ForStmt ("for {} {}", Cond, Body) {
+        Cond: Expr,
+        Body: StmtBlock,
+    },
+
+    // `for x : iter { ... }`, iter being any iterable such as the range `0..n`.
+    ForInStmt ("for {} : {} {}", Name, Iter, Body) {
+        Name: Ident,
+        Iter: Expr,
+        Body: StmtBlock,
+    },
+
+    // `break` or `continue`, with an optional label.
+    BranchStmt ("{} {}", Tok, Label) {
+        Tok: TokenKind,
+        Label: Optional<Ident>,
+    },
+
+    ReturnStmt ("return {}", Result) {
+        Result: Expr,
+    },
+
+    // `x = y`, or a compound assignment such as `x += y`.
+    AssignStmt ("{} {} {}", Lhs, Op, Rhs) {
+        Lhs: Expr,
+        Op: TokenKind,
+        Rhs: Expr,
     }
 }
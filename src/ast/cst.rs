@@ -0,0 +1,270 @@
+// Copyright 2024 LangVM Project
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+
+use crate::ast::*;
+use crate::parser::{AstNodeParserTrait, Parser};
+use crate::scanner::*;
+use crate::tag_matches;
+
+// Concrete syntax tree: every token keeps its source text and trivia, and tokens are nested under
+// the grammar nodes the parser built over them, with bracketed groups inside. Printing a Cst gives
+// back the source byte-for-byte, syntax errors included.
+
+pub struct CstToken<'a> {
+    pub Pos: PosRange,
+    pub Kind: TokenKind,
+    pub Leading: Vec<Trivia<'a>>,
+    pub Text: &'a str,
+    pub Trailing: Vec<Trivia<'a>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CstKind {
+    File,
+    // A grammar node, named by its AST type, e.g. `FuncDecl` or `BinaryExpr`
+    Node(&'static str),
+    // Delimited by `(` `)`, `[` `]` or `{` `}`; the closing token is missing when unterminated.
+    Group,
+    // Closing delimiter without a matching opening one
+    Error,
+}
+
+pub enum CstElement<'a> {
+    Node(CstNode<'a>),
+    Token(CstToken<'a>),
+}
+
+pub struct CstNode<'a> {
+    pub Kind: CstKind,
+    pub Children: Vec<CstElement<'a>>,
+}
+
+pub struct Cst<'a> {
+    pub Root: CstNode<'a>,
+    // Trivia after the last token
+    pub Trailing: Vec<Trivia<'a>>,
+}
+
+fn Closing(kind: &TokenKind) -> Option<TokenKind> {
+    match kind {
+        TokenKind::LPAREN => { Some(TokenKind::RPAREN) }
+        TokenKind::LBRACK => { Some(TokenKind::RBRACK) }
+        TokenKind::LBRACE => { Some(TokenKind::RBRACE) }
+        _ => { None }
+    }
+}
+
+// Collects the grammar nodes of an AST in preorder, each with the source it covers.
+pub trait CstSpans {
+    fn Spans(&self, spans: &mut Vec<(&'static str, PosRange)>);
+}
+
+macro_rules! def_leaf_spans {
+    ($($typ:ty), *) => {
+        $(
+        impl CstSpans for $typ {
+            fn Spans(&self, _: &mut Vec<(&'static str, PosRange)>) {}
+        }
+        )*
+    };
+}
+
+def_leaf_spans!(bool, String, Token, TokenKind);
+
+impl<T: CstSpans> CstSpans for Box<T> {
+    fn Spans(&self, spans: &mut Vec<(&'static str, PosRange)>) { self.as_ref().Spans(spans) }
+}
+
+impl<T: CstSpans> CstSpans for Vec<T> {
+    fn Spans(&self, spans: &mut Vec<(&'static str, PosRange)>) {
+        for e in self {
+            e.Spans(spans);
+        }
+    }
+}
+
+impl<T: CstSpans> CstSpans for List<T> {
+    fn Spans(&self, spans: &mut Vec<(&'static str, PosRange)>) { self.Elements.Spans(spans) }
+}
+
+impl<T: CstSpans> CstSpans for Optional<T> {
+    fn Spans(&self, spans: &mut Vec<(&'static str, PosRange)>) {
+        if let Optional::Some(e) = self {
+            e.Spans(spans);
+        }
+    }
+}
+
+// Nodes with a hand-written Display, so not covered by def_ast!
+macro_rules! def_node_spans {
+    ($($ast:ident { $($name:ident), * }), *) => {
+        $(
+        impl CstSpans for $ast {
+            fn Spans(&self, spans: &mut Vec<(&'static str, PosRange)>) {
+                spans.push((stringify!($ast), self.Pos));
+                $(
+                self.$name.Spans(spans);
+                )*
+            }
+        }
+        )*
+    };
+}
+
+def_node_spans! {
+    Receiver {},
+    RefType { Elem },
+    InterpolationExpr { Parts },
+    TupleExpr { Elements },
+    BorrowExpr { Expr },
+    StructLit { Type, Fields, Base },
+    Path { Segments },
+    UnionPattern { Variant, Elements },
+    FuncType { Receiver, Params, Result }
+}
+
+impl CstNode<'_> {
+    // Closing delimiter this group waits for
+    fn Want(&self) -> Option<TokenKind> {
+        match (&self.Kind, self.Children.first()) {
+            (CstKind::Group, Some(CstElement::Token(open))) => { Closing(&open.Kind) }
+            _ => { None }
+        }
+    }
+}
+
+impl<'a> Cst<'a> {
+    pub fn Parse(buffer: &'a str) -> Cst<'a> {
+        // grammar nodes come from parsing the buffer; a node without tokens has nothing to hold
+        let mut spans: Vec<(&'static str, PosRange)> = vec![];
+        let mut p = Parser::new(buffer);
+        p.Bump();
+        if let Ok(file) = File::Expect(&mut p) {
+            file.Spans(&mut spans);
+        }
+        spans.retain(|(kind, pos)| *kind != "File" && pos.Begin.Offset < pos.End.Offset);
+        spans.sort_by_key(|(_, pos)| (pos.Begin.Offset, std::cmp::Reverse(pos.End.Offset)));
+        let mut spans = spans.into_iter().peekable();
+
+        let mut p = Parser::new(buffer);
+
+        let mut stack: Vec<CstNode<'a>> = vec![CstNode { Kind: CstKind::File, Children: vec![] }];
+        // End offset of every open grammar node, innermost last
+        let mut ends: Vec<usize> = vec![];
+
+        let eof = loop {
+            let lt = match p.Scanner.ScanLossless() {
+                Ok(lt) => { lt }
+                Err(eof) => { break eof; }
+            };
+            let begin = lt.Token.Pos.Begin.Offset;
+
+            // close the nodes ending before this token, with any group left open inside them
+            while ends.last().map_or(false, |end| *end <= begin) {
+                ends.pop();
+                loop {
+                    let node = stack.pop().unwrap();
+                    let done = matches!(node.Kind, CstKind::Node(_));
+                    stack.last_mut().unwrap().Children.push(CstElement::Node(node));
+                    if done {
+                        break;
+                    }
+                }
+            }
+            while spans.peek().map_or(false, |(_, pos)| pos.Begin.Offset < begin) {
+                spans.next();
+            }
+            while let Some((kind, pos)) = spans.next_if(|(_, pos)| pos.Begin.Offset == begin) {
+                stack.push(CstNode { Kind: CstKind::Node(kind), Children: vec![] });
+                ends.push(pos.End.Offset);
+            }
+
+            let tok = CstToken {
+                Pos: lt.Token.Pos,
+                Kind: TokenKind::Classify(&lt.Token.Kind, &lt.Token.Literal, &p.KeywordLookup),
+                Leading: lt.Leading,
+                Text: lt.Text,
+                Trailing: lt.Trailing,
+            };
+
+            match &tok.Kind {
+                TokenKind::LPAREN | TokenKind::LBRACK | TokenKind::LBRACE => {
+                    stack.push(CstNode { Kind: CstKind::Group, Children: vec![CstElement::Token(tok)] });
+                }
+                TokenKind::RPAREN | TokenKind::RBRACK | TokenKind::RBRACE => {
+                    match stack.iter().rposition(|it| it.Want().map_or(false, |want| tag_matches!(&want, &tok.Kind))) {
+                        Some(i) => {
+                            // groups opened after the matching one are unterminated, nodes inside it end with it
+                            while stack.len() > i + 1 {
+                                let node = stack.pop().unwrap();
+                                if matches!(node.Kind, CstKind::Node(_)) {
+                                    ends.pop();
+                                }
+                                stack.last_mut().unwrap().Children.push(CstElement::Node(node));
+                            }
+                            let mut node = stack.pop().unwrap();
+                            node.Children.push(CstElement::Token(tok));
+                            stack.last_mut().unwrap().Children.push(CstElement::Node(node));
+                        }
+                        None => {
+                            let node = CstNode { Kind: CstKind::Error, Children: vec![CstElement::Token(tok)] };
+                            stack.last_mut().unwrap().Children.push(CstElement::Node(node));
+                        }
+                    }
+                }
+                _ => { stack.last_mut().unwrap().Children.push(CstElement::Token(tok)); }
+            }
+        };
+
+        while stack.len() > 1 {
+            let node = stack.pop().unwrap();
+            stack.last_mut().unwrap().Children.push(CstElement::Node(node));
+        }
+
+        Cst { Root: stack.pop().unwrap(), Trailing: eof.Leading }
+    }
+}
+
+impl fmt::Display for Trivia<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.Text)
+    }
+}
+
+impl fmt::Display for CstToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for e in &self.Leading {
+            write!(f, "{}", e)?;
+        }
+        write!(f, "{}", self.Text)?;
+        for e in &self.Trailing {
+            write!(f, "{}", e)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CstNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for e in &self.Children {
+            match e {
+                CstElement::Node(node) => { write!(f, "{}", node)?; }
+                CstElement::Token(tok) => { write!(f, "{}", tok)?; }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Cst<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.Root)?;
+        for e in &self.Trailing {
+            write!(f, "{}", e)?;
+        }
+        Ok(())
+    }
+}
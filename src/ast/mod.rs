@@ -3,10 +3,12 @@
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
 pub use ast::*;
+pub use cst::*;
 pub use parse::*;
 pub use token::*;
 
 pub mod ast;
+pub mod cst;
 pub mod parse;
 pub mod token;
 mod parse_test;
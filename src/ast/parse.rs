@@ -8,7 +8,7 @@ use crate::parser::*;
 
 macro_rules! range {
     ($begin: expr, $self: expr) => {
-        crate::scanner::PosRange { Begin: $begin, End: $self.GetPos() }
+        crate::scanner::PosRange { File: $self.Scanner.BufferScanner.File, Begin: $begin, End: $self.LastEnd }
     };
 }
 
@@ -21,22 +21,16 @@ macro_rules! match_terms {
 }
 
 impl<T> List<T> where T: AstNodeParserTrait<T> {
+    // Parses elements separated by `delimiter` up to `term`, which is left for the caller to match.
+    // A trailing delimiter is allowed.
     pub fn Expect(p: &mut Parser, delimiter: TokenKind, term: TokenKind) -> Result<List<T>, ParserError> {
         let begin = p.GetPos();
 
         let mut list: Vec<T> = vec![];
-        loop {
+        while !p.At(term.clone()) && !p.At(TokenKind::EOF) {
             list.push(T::Expect(p)?);
-            match &p.Token.Kind {
-                it if tag_matches!(it, &delimiter) => {
-                    p.Scan()?;
-                    match &p.Token.Kind {
-                        it if tag_matches!(it, &term) => { break; }
-                        _ => {}
-                    }
-                }
-                it if tag_matches!(it, &term) => { break; }
-                _ => {}
+            if !p.Eat(delimiter.clone()) {
+                break;
             }
         }
         Ok(List {
@@ -48,15 +42,428 @@ impl<T> List<T> where T: AstNodeParserTrait<T> {
     }
 }
 
+// Binary operator precedence, loosest first; all binary operators are left-associative.
+//
+//   1  ..
+//   2  ||
+//   3  &&
+//   4  ==  !=  <  >  <=  >=
+//   5  |
+//   6  ^
+//   7  &
+//   8  <<  >>
+//   9  +  -
+//  10  *  /  %
+//
+// Prefix operators `-` `!` `~` `&` bind tighter than any binary operator, and postfix call `f(x)`,
+// index `a[i]`, field `a.b` and unwrap `a?` bind tighter still.
+fn BinaryPrecedence(kind: &TokenKind) -> Option<u8> {
+    match kind {
+        TokenKind::RANGE => { Some(1) }
+        TokenKind::LOR => { Some(2) }
+        TokenKind::LAND => { Some(3) }
+        TokenKind::EQL | TokenKind::NEQ | TokenKind::LSS | TokenKind::GTR | TokenKind::LEQ | TokenKind::GEQ => { Some(4) }
+        TokenKind::OR => { Some(5) }
+        TokenKind::XOR => { Some(6) }
+        TokenKind::AND => { Some(7) }
+        TokenKind::SHL | TokenKind::SHR => { Some(8) }
+        TokenKind::ADD | TokenKind::SUB => { Some(9) }
+        TokenKind::MUL | TokenKind::QUO | TokenKind::REM => { Some(10) }
+        _ => { None }
+    }
+}
+
+impl Expr {
+    // Precedence climbing: parses operands joined by binary operators that bind tighter than `min`.
+    pub fn ExpectBinary(p: &mut Parser, min: u8) -> Result<Expr, ParserError> {
+        let begin = p.GetPos();
+
+        let mut x = Expr::ExpectUnary(p)?;
+        loop {
+            let precedence = match BinaryPrecedence(&p.Token.Kind) {
+                Some(precedence) if precedence > min => { precedence }
+                _ => { break; }
+            };
+            let op = p.Bump().Kind;
+            let y = Expr::ExpectBinary(p, precedence)?;
+            x = Expr::BinaryExpr(Box::new(BinaryExpr { X: x, Op: op, Y: y, Pos: range![begin, p] }));
+        }
+        Ok(x)
+    }
+
+    pub fn ExpectUnary(p: &mut Parser) -> Result<Expr, ParserError> {
+        let begin = p.GetPos();
+
+        match p.Token.Kind {
+            TokenKind::SUB | TokenKind::NOT | TokenKind::TILDE => {
+                let op = p.Bump().Kind;
+                let x = Expr::ExpectUnary(p)?;
+                Ok(Expr::UnaryExpr(Box::new(UnaryExpr { Op: op, Expr: x, Pos: range![begin, p] })))
+            }
+            // `&&x` is scanned as one token, borrowing twice
+            TokenKind::AND | TokenKind::LAND => {
+                let twice = p.At(TokenKind::LAND);
+                p.Bump();
+                let mutable = p.Eat(TokenKind::MUT);
+                let x = Expr::ExpectUnary(p)?;
+                let borrow = Expr::BorrowExpr(Box::new(BorrowExpr { Mutable: mutable, Expr: x, Pos: range![begin, p] }));
+                Ok(match twice {
+                    true => { Expr::BorrowExpr(Box::new(BorrowExpr { Mutable: false, Expr: borrow, Pos: range![begin, p] })) }
+                    false => { borrow }
+                })
+            }
+            _ => { Expr::ExpectPostfix(p) }
+        }
+    }
+
+    // An operand followed by any number of calls, indexes, field accesses, unwraps and type arguments,
+    // or by the fields of a struct literal after a type name.
+    pub fn ExpectPostfix(p: &mut Parser) -> Result<Expr, ParserError> {
+        let begin = p.GetPos();
+
+        let mut x = Expr::ExpectOperand(p)?;
+        loop {
+            let typeArgs = p.At(TokenKind::LSS) && Type::AtArgs(p);
+            x = match p.Token.Kind {
+                TokenKind::LPAREN => {
+                    p.Bump();
+                    let params = p.WithStructLiteral(true, |p| List::Expect(p, TokenKind::COMMA, TokenKind::RPAREN))?;
+                    p.MatchTerm(TokenKind::RPAREN)?;
+                    Expr::CallExpr(Box::new(CallExpr { Callee: x, Params: params, Pos: range![begin, p] }))
+                }
+                TokenKind::LBRACK => {
+                    p.Bump();
+                    let index = p.WithStructLiteral(true, |p| Expr::Expect(p))?;
+                    p.MatchTerm(TokenKind::RBRACK)?;
+                    Expr::IndexExpr(Box::new(IndexExpr { Expr: x, Index: index, Pos: range![begin, p] }))
+                }
+                TokenKind::DOT => {
+                    p.Bump();
+                    match TupleIndexExpr::AtIndex(p) {
+                        true => { TupleIndexExpr::ExpectIndex(p, begin, x)? }
+                        false => {
+                            let sel = Ident::Expect(p)?;
+                            Expr::SelectorExpr(Box::new(SelectorExpr { Expr: x, Sel: sel, Pos: range![begin, p] }))
+                        }
+                    }
+                }
+                TokenKind::LBRACE if !p.NoStructLiteral && matches!(x, Expr::Ident(_) | Expr::SelectorExpr(_)) => {
+                    Expr::StructLit(Box::new(StructLit::ExpectBody(p, begin, x)?))
+                }
+                TokenKind::LSS if typeArgs => {
+                    let args = Type::ExpectArgs(p)?;
+                    Expr::GenericExpr(Box::new(GenericExpr { Expr: x, Args: args, Pos: range![begin, p] }))
+                }
+                TokenKind::QUESTION => {
+                    p.Bump();
+                    Expr::UnwrapExpr(Box::new(UnwrapExpr { Expr: x, Pos: range![begin, p] }))
+                }
+                _ => { break; }
+            };
+        }
+        Ok(x)
+    }
+
+    pub fn ExpectOperand(p: &mut Parser) -> Result<Expr, ParserError> {
+        let begin = p.GetPos();
+
+        Ok(match p.Token.Kind {
+            TokenKind::Ident => { Expr::Ident(Box::new(Ident::Expect(p)?)) }
+            // an Invalid token was already reported by the scanner, taking it as a literal avoids a second error
+            TokenKind::Int(_) | TokenKind::Float(_) | TokenKind::Char | TokenKind::String | TokenKind::Invalid => {
+                let tok = p.Bump();
+                Expr::LiteralValue(Box::new(LiteralValue { Pos: tok.Pos, Token: tok }))
+            }
+            TokenKind::StringHead => { Expr::InterpolationExpr(Box::new(InterpolationExpr::Expect(p)?)) }
+            TokenKind::IF => { Expr::IfExpr(Box::new(IfExpr::Expect(p)?)) }
+            TokenKind::MATCH => { Expr::MatchExpr(Box::new(MatchExpr::Expect(p)?)) }
+            TokenKind::LBRACE => { Expr::StmtBlock(Box::new(StmtBlock::Expect(p)?)) }
+            // parenthesized, or a tuple when empty or holding a comma
+            TokenKind::LPAREN => {
+                p.Bump();
+                match p.At(TokenKind::RPAREN) {
+                    true => {
+                        p.Bump();
+                        Expr::TupleExpr(Box::new(TupleExpr { Elements: List::default(), Pos: range![begin, p] }))
+                    }
+                    false => {
+                        let x = p.WithStructLiteral(true, |p| Expr::Expect(p))?;
+                        match p.Eat(TokenKind::COMMA) {
+                            true => {
+                                let mut elements = p.WithStructLiteral(true, |p| List::Expect(p, TokenKind::COMMA, TokenKind::RPAREN))?;
+                                elements.Elements.insert(0, x);
+                                p.MatchTerm(TokenKind::RPAREN)?;
+                                Expr::TupleExpr(Box::new(TupleExpr { Elements: elements, Pos: range![begin, p] }))
+                            }
+                            false => {
+                                p.MatchTerm(TokenKind::RPAREN)?;
+                                Expr::ParenExpr(Box::new(ParenExpr { Expr: x, Pos: range![begin, p] }))
+                            }
+                        }
+                    }
+                }
+            }
+            TokenKind::LBRACK => {
+                p.Bump();
+                let elements = p.WithStructLiteral(true, |p| List::Expect(p, TokenKind::COMMA, TokenKind::RBRACK))?;
+                p.MatchTerm(TokenKind::RBRACK)?;
+                Expr::ArrayExpr(Box::new(ArrayExpr { Elements: elements, Pos: range![begin, p] }))
+            }
+            _ => {
+                p.Report(SyntaxError::UnexpectedNode(UnexpectedNodeError { Pos: p.Token.Pos, Want: Node::TokenKind(TokenKind::Ident), Have: Node::Token(p.Token.clone()) }));
+                match p.Token.Kind {
+                    TokenKind::RPAREN | TokenKind::RBRACK | TokenKind::RBRACE | TokenKind::SEMICOLON | TokenKind::COMMA | TokenKind::EOF => {}
+                    _ => { p.Bump(); }
+                }
+                Expr::None
+            }
+        })
+    }
+}
+
+impl TupleIndexExpr {
+    // A plain decimal after `.`, or `0.1` scanned as one float in `t.0.1`.
+    pub fn AtIndex(p: &mut Parser) -> bool {
+        let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        match p.Token.Kind {
+            TokenKind::Int(crate::scanner::IntFormat::DEC) => { digits(&p.Token.Literal) }
+            TokenKind::Float(crate::scanner::IntFormat::DEC) => {
+                matches!(p.Token.Literal.split_once('.'), Some((a, b)) if digits(a) && digits(b))
+            }
+            _ => { false }
+        }
+    }
+
+    pub fn ExpectIndex(p: &mut Parser, begin: crate::scanner::Position, x: Expr) -> Result<Expr, ParserError> {
+        let tok = p.Bump();
+        let (first, second) = match tok.Literal.split_once('.') {
+            Some((a, b)) => { (a, Some(b)) }
+            None => { (tok.Literal.as_ref(), None) }
+        };
+
+        // the indexes of a split float sit on one line, either side of its dot
+        let at = |n: usize| crate::scanner::Position { Offset: tok.Pos.Begin.Offset + n, Line: tok.Pos.Begin.Line, Column: tok.Pos.Begin.Column + n };
+        let index = |p: &mut Parser, from: usize, literal: &str| Token {
+            Pos: crate::scanner::PosRange { File: tok.Pos.File, Begin: at(from), End: at(from + literal.len()) },
+            Kind: TokenKind::Int(crate::scanner::IntFormat::DEC),
+            Literal: p.Intern(literal),
+        };
+
+        let firstIndex = index(p, 0, first);
+        let end = firstIndex.Pos.End;
+        let x = Expr::TupleIndexExpr(Box::new(TupleIndexExpr {
+            Expr: x,
+            Index: firstIndex,
+            Pos: crate::scanner::PosRange { File: tok.Pos.File, Begin: begin, End: end },
+        }));
+        Ok(match second {
+            Some(second) => {
+                let secondIndex = index(p, first.len() + 1, second);
+                Expr::TupleIndexExpr(Box::new(TupleIndexExpr { Expr: x, Index: secondIndex, Pos: range![begin, p] }))
+            }
+            None => { x }
+        })
+    }
+}
+
+impl StructLit {
+    // Fields after the type of a struct literal, from `{` to `}`.
+    pub fn ExpectBody(p: &mut Parser, begin: crate::scanner::Position, typ: Expr) -> Result<StructLit, ParserError> {
+        let fieldsBegin = p.GetPos();
+        p.MatchTerm(TokenKind::LBRACE)?;
+
+        // fields end with a comma, or with a newline completed to a semicolon; `..base` comes last
+        let mut fields: Vec<FieldValue> = vec![];
+        let mut base = Expr::None;
+        p.WithStructLiteral(true, |p| {
+            while !p.At(TokenKind::RBRACE) && !p.At(TokenKind::EOF) {
+                if p.Eat(TokenKind::RANGE) {
+                    base = Expr::Expect(p)?;
+                    p.Eat(TokenKind::COMMA);
+                    p.Eat(TokenKind::SEMICOLON);
+                    break;
+                }
+                fields.push(FieldValue::Expect(p)?);
+                if !p.Eat(TokenKind::COMMA) && !p.Eat(TokenKind::SEMICOLON) {
+                    break;
+                }
+            }
+            Ok(())
+        })?;
+        p.MatchTerm(TokenKind::RBRACE)?;
+
+        Ok(StructLit {
+            Type: typ,
+            Fields: List { Pos: range![fieldsBegin, p], Elements: fields, Delimiter: TokenKind::COMMA, Term: TokenKind::RBRACE },
+            Base: base,
+            Pos: range![begin, p],
+        })
+    }
+}
+
+impl FuncDecl {
+    // Name, type and optional body of a function whose `func` keyword, if any, was consumed.
+    pub fn ExpectSignature(p: &mut Parser, begin: crate::scanner::Position, doc: Optional<Doc>) -> Result<FuncDecl, ParserError> {
+        let name = Ident::Expect(p)?;
+        let typeParams = TypeParam::ExpectParams(p)?;
+        let typ = FuncType::Expect(p)?;
+        let where_ = TypeParam::ExpectWhere(p)?;
+
+        // bodiless in traits and for external functions
+        let body = match p.At(TokenKind::LBRACE) {
+            true => { Optional::Some(StmtBlock::Expect(p)?) }
+            false => { Optional::None }
+        };
+
+        Ok(FuncDecl {
+            Doc: doc,
+            Name: Optional::Some(name),
+            TypeParams: typeParams,
+            Where: where_,
+            Type: typ,
+            Stmt: body,
+            Pos: range![begin, p],
+        })
+    }
+}
+
+impl Type {
+    // Whether the `<` at the current token opens type arguments rather than comparing, as in `Max<int>(a, b)`:
+    // the tokens up to the matching `>` must all be possible in types, and a `(` must follow it.
+    pub fn AtArgs(p: &mut Parser) -> bool {
+        let mut depth = 0;
+        let mut n = 0;
+        loop {
+            match p.Peek(n).Kind {
+                TokenKind::LSS => { depth += 1; }
+                TokenKind::GTR => { depth -= 1; }
+                TokenKind::SHR => { depth -= 2; }
+                TokenKind::Ident | TokenKind::COMMA | TokenKind::SCOPE | TokenKind::AND | TokenKind::MUT
+                | TokenKind::LBRACK | TokenKind::RBRACK | TokenKind::QUESTION => {}
+                _ => { return false; }
+            }
+            n += 1;
+            if depth < 0 {
+                return false;
+            }
+            if depth == 0 {
+                return tag_matches!(&p.Peek(n).Kind, &TokenKind::LPAREN);
+            }
+        }
+    }
+
+    // `<int, List<T>>`
+    pub fn ExpectArgs(p: &mut Parser) -> Result<List<Type>, ParserError> {
+        p.MatchTerm(TokenKind::LSS)?;
+        let args = List::Expect(p, TokenKind::COMMA, TokenKind::GTR)?;
+        if !p.EatClosingGreater() {
+            p.MatchTerm(TokenKind::GTR)?;
+        }
+        Ok(args)
+    }
+}
+
+impl TypeParam {
+    // `<T, U: Ord + Copy>`, or none without `<`.
+    pub fn ExpectParams(p: &mut Parser) -> Result<List<TypeParam>, ParserError> {
+        if !p.At(TokenKind::LSS) {
+            return Ok(List::default());
+        }
+        p.Bump();
+        let params = List::Expect(p, TokenKind::COMMA, TokenKind::GTR)?;
+        if !p.EatClosingGreater() {
+            p.MatchTerm(TokenKind::GTR)?;
+        }
+        Ok(params)
+    }
+
+    // `where T: Ord, U: Copy`, or none without `where`.
+    pub fn ExpectWhere(p: &mut Parser) -> Result<List<TypeParam>, ParserError> {
+        match p.Eat(TokenKind::WHERE) {
+            true => { List::Expect(p, TokenKind::COMMA, TokenKind::None) }
+            false => { Ok(List::default()) }
+        }
+    }
+}
+
+impl Receiver {
+    // Whether a `self`, `&self` or `&mut self` receiver starts at the current token.
+    pub fn At(p: &mut Parser) -> bool {
+        let mutable = tag_matches!(&p.Peek(1).Kind, &TokenKind::MUT);
+        let n = match (p.At(TokenKind::AND), mutable) {
+            (true, true) => { 2 }
+            (true, false) => { 1 }
+            _ => { 0 }
+        };
+        let tok = p.Peek(n);
+        tag_matches!(&tok.Kind, &TokenKind::Ident) && tok.Literal.as_ref() == "self"
+    }
+}
+
+impl Pattern {
+    // A pattern without `|` alternatives.
+    pub fn ExpectSingle(p: &mut Parser) -> Result<Pattern, ParserError> {
+        let begin = p.GetPos();
+
+        Ok(match p.Token.Kind {
+            TokenKind::Ident if p.Token.Literal.as_ref() == "_" => {
+                let tok = p.Bump();
+                Pattern::WildcardPattern(Box::new(WildcardPattern { Pos: tok.Pos, Token: tok }))
+            }
+            TokenKind::Ident => {
+                let path = Path::Expect(p)?;
+                match p.Token.Kind {
+                    TokenKind::LPAREN => {
+                        p.Bump();
+                        let elements = List::Expect(p, TokenKind::COMMA, TokenKind::RPAREN)?;
+                        p.MatchTerm(TokenKind::RPAREN)?;
+                        Pattern::UnionPattern(Box::new(UnionPattern { Variant: path, Elements: elements, Pos: range![begin, p] }))
+                    }
+                    TokenKind::LBRACE => {
+                        p.Bump();
+                        let fields = List::Expect(p, TokenKind::COMMA, TokenKind::RBRACE)?;
+                        p.MatchTerm(TokenKind::RBRACE)?;
+                        Pattern::StructPattern(Box::new(StructPattern { Type: path, Fields: fields, Pos: range![begin, p] }))
+                    }
+                    _ if path.Segments.len() == 1 => {
+                        let name = path.Segments.into_iter().next().unwrap();
+                        Pattern::BindingPattern(Box::new(BindingPattern { Name: name, Pos: range![begin, p] }))
+                    }
+                    // qualified unit variant, `Option::None`
+                    _ => { Pattern::UnionPattern(Box::new(UnionPattern { Variant: path, Elements: List::default(), Pos: range![begin, p] })) }
+                }
+            }
+            TokenKind::Int(_) | TokenKind::Float(_) | TokenKind::Char | TokenKind::String | TokenKind::SUB => {
+                let low = LiteralPattern::Expect(p)?;
+                match p.Eat(TokenKind::RANGE) {
+                    true => { Pattern::RangePattern(Box::new(RangePattern { Low: low, High: LiteralPattern::Expect(p)?, Pos: range![begin, p] })) }
+                    false => { Pattern::LiteralPattern(Box::new(low)) }
+                }
+            }
+            TokenKind::LPAREN => {
+                p.Bump();
+                let elements = List::Expect(p, TokenKind::COMMA, TokenKind::RPAREN)?;
+                p.MatchTerm(TokenKind::RPAREN)?;
+                Pattern::TuplePattern(Box::new(TuplePattern { Elements: elements, Pos: range![begin, p] }))
+            }
+            _ => {
+                p.ReportAndRecover(SyntaxError::UnexpectedNode(UnexpectedNodeError { Pos: p.Token.Pos, Want: Node::TokenKind(TokenKind::Ident), Have: Node::Token(p.Token.clone()) }))?;
+                Pattern::None
+            }
+        })
+    }
+}
+
 def_parser! {
     Ident, p => {
         match p.Token.Kind {
             TokenKind::Ident => {
-                if p.NamespaceIdents.contains_key(&p.Token.Literal) {}
-                Ident { Pos: p.Token.Pos, Token: p.Token.clone() }
+                if p.NamespaceIdents.contains_key(&*p.Token.Literal) {}
+                let tok = p.Bump();
+                Ident { Pos: tok.Pos, Token: tok }
             }
             _ => {
-                p.ReportAndRecover(SyntaxError::UnexpectedNode(UnexpectedNodeError { Want: Default::default(), Have: Default::default() }))?;
+                p.ReportAndRecover(SyntaxError::UnexpectedNode(UnexpectedNodeError { Pos: p.Token.Pos, Want: Node::TokenKind(TokenKind::Ident), Have: Node::Token(p.Token.clone()) }))?;
                 Ident::default()
             }
         }
@@ -66,6 +473,7 @@ def_parser! {
         let begin = p.GetPos();
         
         Field {
+            Doc: p.TakeDoc(),
             Names: List::Expect(p, TokenKind::COMMA, TokenKind::None)?,
             Type: Type::Expect(p)?,
             Pos: range![begin, p],
@@ -74,169 +482,726 @@ def_parser! {
 
     Type, p => {
         match p.Token.Kind {
-            TokenKind::Ident => {  }
+            TokenKind::Ident => {
+                let begin = p.GetPos();
+                let base = Ident::Expect(p)?;
+                match p.At(TokenKind::LSS) {
+                    true => { Type::GenericType(Box::new(GenericType { Base: base, Args: Type::ExpectArgs(p)?, Pos: range![begin, p] })) }
+                    false => { Type::Ident(Box::new(base)) }
+                }
+            }
             TokenKind::STRUCT => { Type::StructType(Box::new(StructType::Expect(p)?)) }
             TokenKind::TRAIT => { Type::TraitType(Box::new(TraitType::Expect(p)?)) }
-            TokenKind::FUNC => {  }
+            TokenKind::UNION => { Type::UnionType(Box::new(UnionType::Expect(p)?)) }
+            TokenKind::FUNC => {
+                p.Bump();
+                Type::FuncType(Box::new(FuncType::Expect(p)?))
+            }
+            TokenKind::LBRACK => {
+                let begin = p.GetPos();
+                p.Bump();
+                match p.Eat(TokenKind::RBRACK) {
+                    true => { Type::SliceType(Box::new(SliceType { Elem: Type::Expect(p)?, Pos: range![begin, p] })) }
+                    false => {
+                        let len = Expr::Expect(p)?;
+                        p.MatchTerm(TokenKind::RBRACK)?;
+                        Type::ArrayType(Box::new(ArrayType { Len: len, Elem: Type::Expect(p)?, Pos: range![begin, p] }))
+                    }
+                }
+            }
+            TokenKind::LPAREN => {
+                let begin = p.GetPos();
+                p.Bump();
+                let elements = List::Expect(p, TokenKind::COMMA, TokenKind::RPAREN)?;
+                p.MatchTerm(TokenKind::RPAREN)?;
+                Type::TupleType(Box::new(TupleType { Elements: elements, Pos: range![begin, p] }))
+            }
+            TokenKind::QUESTION => {
+                let begin = p.GetPos();
+                p.Bump();
+                Type::OptionalType(Box::new(OptionalType { Elem: Type::Expect(p)?, Pos: range![begin, p] }))
+            }
+            TokenKind::AND | TokenKind::LAND => { Type::RefType(Box::new(RefType::Expect(p)?)) }
             _ => {
-                p.ReportAndRecover(SyntaxError::UnexpectedNode(UnexpectedNodeError { Want: todo!(), Have: todo!() }))?;
+                p.ReportAndRecover(SyntaxError::UnexpectedNode(UnexpectedNodeError { Pos: p.Token.Pos, Want: Node::TokenKind(TokenKind::Ident), Have: Node::Token(p.Token.clone()) }))?;
                 Type::None
             }
         }
     },
 
-    FuncType, p => {
+    // `&T`, `&mut T`, or `&&T` scanned as one token.
+    RefType, p => {
         let begin = p.GetPos();
 
-        p.MatchTerm(TokenKind::LPAREN)?;
+        let twice = p.At(TokenKind::LAND);
+        p.Bump();
+        let mutable = p.Eat(TokenKind::MUT);
+        let ref_ = RefType { Mutable: mutable, Elem: Type::Expect(p)?, Pos: range![begin, p] };
 
-        let params = List::Expect(p,TokenKind::COMMA, TokenKind::RPAREN)?;
+        match twice {
+            true => { RefType { Mutable: false, Elem: Type::RefType(Box::new(ref_)), Pos: range![begin, p] } }
+            false => { ref_ }
+        }
+    },
 
-        match p.Token.Kind {
-            TokenKind::PASS => {
-                FuncType {
-                    Params: params,
-                    Result: Type::Expect(p)?,
-                    Pos: range![begin, p],
-                }
-            }
-            _ => {
-                FuncType {
-                    Params: params,
-                    Result: Type::None,
-                    Pos: range![begin, p],
+    // Parameters and result, `(a, b int) <- int`.
+    FuncType, p => {
+        let begin = p.GetPos();
+
+        p.MatchTerm(TokenKind::LPAREN)?;
+        let receiver = match Receiver::At(p) {
+            true => {
+                let receiver = Receiver::Expect(p)?;
+                if !p.At(TokenKind::RPAREN) {
+                    p.MatchTerm(TokenKind::COMMA)?;
                 }
+                Optional::Some(receiver)
             }
+            false => { Optional::None }
+        };
+        let params = List::Expect(p, TokenKind::COMMA, TokenKind::RPAREN)?;
+        p.MatchTerm(TokenKind::RPAREN)?;
+
+        let result = match p.Eat(TokenKind::PASS) {
+            true => { Type::Expect(p)? }
+            false => { Type::None }
+        };
+
+        FuncType {
+            Receiver: receiver,
+            Params: params,
+            Result: result,
+            Pos: range![begin, p],
         }
     },
 
     StructType, p => {
         let begin = p.GetPos();
+        let doc = p.TakeDoc();
+
+        p.MatchTerm(TokenKind::STRUCT)?;
+        let name = Ident::Expect(p)?;
+        let typeParams = TypeParam::ExpectParams(p)?;
+        let where_ = TypeParam::ExpectWhere(p)?;
 
-        match_terms![p, TokenKind::STRUCT, TokenKind::LBRACE];
+        p.MatchTerm(TokenKind::LBRACE)?;
+        let fieldList = List::Expect(p, TokenKind::SEMICOLON, TokenKind::RBRACE)?;
+        p.MatchTerm(TokenKind::RBRACE)?;
 
         StructType {
-            Name: Ident::Expect(p)?,
-            FieldList: List::Expect(p, TokenKind::SEMICOLON, TokenKind::RBRACE)?,
+            Doc: doc,
+            Name: name,
+            TypeParams: typeParams,
+            Where: where_,
+            FieldList: fieldList,
             Pos: range![begin, p],
         }
     },
 
     TraitType, p => {
         let begin = p.GetPos();
+        let doc = p.TakeDoc();
 
         p.MatchTerm(TokenKind::TRAIT)?;
 
         let name = Ident::Expect(p)?;
+        let typeParams = TypeParam::ExpectParams(p)?;
+        let where_ = TypeParam::ExpectWhere(p)?;
+
+        // method signatures, `func` being optional; a trait without braces has no methods
+        let mut funcList: Vec<FuncDecl> = vec![];
+        let listBegin = p.GetPos();
+        if p.Eat(TokenKind::LBRACE) {
+            while !p.At(TokenKind::RBRACE) && !p.At(TokenKind::EOF) {
+                let begin = p.GetPos();
+                let doc = p.TakeDoc();
+                p.Eat(TokenKind::FUNC);
+                funcList.push(FuncDecl::ExpectSignature(p, begin, doc)?);
+                if !p.Eat(TokenKind::SEMICOLON) {
+                    break;
+                }
+            }
+            p.MatchTerm(TokenKind::RBRACE)?;
+        }
 
         TraitType {
+            Doc: doc,
+            Name: name,
+            TypeParams: typeParams,
+            Where: where_,
+            FuncList: List { Pos: range![listBegin, p], Elements: funcList, Delimiter: TokenKind::SEMICOLON, Term: TokenKind::RBRACE },
+            Pos: range![begin, p],
+        }
+    },
+
+    ImplDecl, p => {
+        let begin = p.GetPos();
+        let doc = p.TakeDoc();
+
+        p.MatchTerm(TokenKind::IMPL)?;
+        let typeParams = TypeParam::ExpectParams(p)?;
+        let trait_ = Type::Expect(p)?;
+        p.MatchTerm(TokenKind::FOR)?;
+        let typ = Type::Expect(p)?;
+        let where_ = TypeParam::ExpectWhere(p)?;
+
+        p.MatchTerm(TokenKind::LBRACE)?;
+        let funcList = List::Expect(p, TokenKind::SEMICOLON, TokenKind::RBRACE)?;
+        p.MatchTerm(TokenKind::RBRACE)?;
+
+        ImplDecl {
+            Doc: doc,
+            TypeParams: typeParams,
+            Where: where_,
+            Trait: trait_,
+            Type: typ,
+            FuncList: funcList,
+            Pos: range![begin, p],
+        }
+    },
+
+    FieldValue, p => {
+        let begin = p.GetPos();
+
+        let name = Ident::Expect(p)?;
+        let value = match p.Eat(TokenKind::COLON) {
+            true => { Expr::Expect(p)? }
+            false => { Expr::Ident(Box::new(Ident { Pos: name.Pos, Token: name.Token.clone() })) }
+        };
+
+        FieldValue {
+            Name: name,
+            Value: value,
+            Pos: range![begin, p],
+        }
+    },
+
+    TypeParam, p => {
+        let begin = p.GetPos();
+
+        let name = Ident::Expect(p)?;
+
+        let boundsBegin = p.GetPos();
+        let mut bounds: Vec<Type> = vec![];
+        if p.Eat(TokenKind::COLON) {
+            bounds.push(Type::Expect(p)?);
+            while p.Eat(TokenKind::ADD) {
+                bounds.push(Type::Expect(p)?);
+            }
+        }
+
+        TypeParam {
             Name: name,
+            Bounds: List { Pos: range![boundsBegin, p], Elements: bounds, Delimiter: TokenKind::ADD, Term: TokenKind::None },
+            Pos: range![begin, p],
+        }
+    },
+
+    Receiver, p => {
+        let begin = p.GetPos();
+
+        let borrow = p.Eat(TokenKind::AND);
+        let mutable = borrow && p.Eat(TokenKind::MUT);
+        p.Bump(); // self
+
+        Receiver {
+            Borrow: borrow,
+            Mutable: mutable,
             Pos: range![begin, p],
         }
     },
     
+    UnionType, p => {
+        let begin = p.GetPos();
+        let doc = p.TakeDoc();
+
+        p.MatchTerm(TokenKind::UNION)?;
+        let name = Ident::Expect(p)?;
+        let typeParams = TypeParam::ExpectParams(p)?;
+        let where_ = TypeParam::ExpectWhere(p)?;
+
+        p.MatchTerm(TokenKind::LBRACE)?;
+        let variantList = List::Expect(p, TokenKind::SEMICOLON, TokenKind::RBRACE)?;
+        p.MatchTerm(TokenKind::RBRACE)?;
+
+        UnionType {
+            Doc: doc,
+            Name: name,
+            TypeParams: typeParams,
+            Where: where_,
+            VariantList: variantList,
+            Pos: range![begin, p],
+        }
+    },
+
+    UnionVariant, p => {
+        let begin = p.GetPos();
+        let doc = p.TakeDoc();
+
+        let name = Ident::Expect(p)?;
+        let fieldList = match p.Eat(TokenKind::LPAREN) {
+            true => {
+                let fieldList = List::Expect(p, TokenKind::COMMA, TokenKind::RPAREN)?;
+                p.MatchTerm(TokenKind::RPAREN)?;
+                fieldList
+            }
+            false => { List::default() }
+        };
+
+        UnionVariant {
+            Doc: doc,
+            Name: name,
+            FieldList: fieldList,
+            Pos: range![begin, p],
+        }
+    },
+
+    // Package header, imports, then top-level declarations in any order.
+    File, p => {
+        let begin = p.GetPos();
+
+        let package = match p.At(TokenKind::PACKAGE) {
+            true => { Optional::Some(PackageClause::Expect(p)?) }
+            false => { Optional::None }
+        };
+
+        let mut imports: Vec<ImportDecl> = vec![];
+        let mut decls: Vec<Decl> = vec![];
+        loop {
+            match p.Token.Kind {
+                TokenKind::EOF => { break; }
+                TokenKind::SEMICOLON => { p.Bump(); }
+                TokenKind::IMPORT => { imports.push(ImportDecl::Expect(p)?); }
+                TokenKind::STRUCT => { decls.push(Decl::StructType(Box::new(StructType::Expect(p)?))); }
+                TokenKind::TRAIT => { decls.push(Decl::TraitType(Box::new(TraitType::Expect(p)?))); }
+                TokenKind::UNION => { decls.push(Decl::UnionType(Box::new(UnionType::Expect(p)?))); }
+                TokenKind::FUNC => { decls.push(Decl::FuncDecl(Box::new(FuncDecl::Expect(p)?))); }
+                TokenKind::CONST => { decls.push(Decl::ConstDecl(Box::new(ConstDecl::Expect(p)?))); }
+                TokenKind::IMPL => { decls.push(Decl::ImplDecl(Box::new(ImplDecl::Expect(p)?))); }
+                // one error for the whole run of garbage, up to the next declaration
+                _ => {
+                    p.Report(SyntaxError::UnexpectedNode(UnexpectedNodeError { Pos: p.Token.Pos, Want: Node::TokenKind(TokenKind::FUNC), Have: Node::Token(p.Token.clone()) }));
+                    while !matches!(p.Token.Kind,
+                        TokenKind::EOF | TokenKind::IMPORT | TokenKind::STRUCT | TokenKind::TRAIT | TokenKind::UNION |
+                        TokenKind::FUNC | TokenKind::CONST | TokenKind::IMPL) {
+                        p.Bump();
+                    }
+                }
+            }
+        }
+
+        let pos = range![begin, p];
+        File {
+            Package: package,
+            Imports: List { Pos: pos, Elements: imports, Delimiter: TokenKind::SEMICOLON, Term: TokenKind::EOF },
+            Decls: List { Pos: pos, Elements: decls, Delimiter: TokenKind::SEMICOLON, Term: TokenKind::EOF },
+            Pos: pos,
+        }
+    },
+
+    PackageClause, p => {
+        let begin = p.GetPos();
+        let doc = p.TakeDoc();
+
+        p.MatchTerm(TokenKind::PACKAGE)?;
+
+        PackageClause {
+            Doc: doc,
+            Name: Ident::Expect(p)?,
+            Pos: range![begin, p],
+        }
+    },
+
+    // `import fmt "std/fmt"`, registering the alias in the parser's namespaces.
     ImportDecl, p => {
         let begin = p.GetPos();
 
         p.MatchTerm(TokenKind::IMPORT)?;
+        let alias = Ident::Expect(p)?;
+        let canonical = p.MatchTerm(TokenKind::String)?;
+        let pos = range![begin, p];
+
+        p.NamespaceIdents.insert(alias.Token.Literal.to_string(), ImportDecl {
+            Alias: Ident { Pos: alias.Pos, Token: alias.Token.clone() },
+            Canonical: canonical.clone(),
+            Pos: pos,
+        });
+
         ImportDecl {
-            Alias: Ident::Expect(p)?,
-            Canonical: p.MatchTerm(TokenKind::String)?.clone(),
+            Alias: alias,
+            Canonical: canonical,
+            Pos: pos,
+        }
+    },
+
+    ConstDecl, p => {
+        let begin = p.GetPos();
+        let doc = p.TakeDoc();
+
+        p.MatchTerm(TokenKind::CONST)?;
+        let name = Ident::Expect(p)?;
+        let typ = match p.At(TokenKind::ASSIGN) {
+            true => { Type::None }
+            false => { Type::Expect(p)? }
+        };
+        p.MatchTerm(TokenKind::ASSIGN)?;
+
+        ConstDecl {
+            Doc: doc,
+            Name: name,
+            Type: typ,
+            Value: Expr::Expect(p)?,
             Pos: range![begin, p],
         }
     },
     
     FuncDecl, p => {
         let begin = p.GetPos();
+        let doc = p.TakeDoc();
 
         p.MatchTerm(TokenKind::FUNC)?;
+        FuncDecl::ExpectSignature(p, begin, doc)?
+    },
+    
+    Expr, p => { Expr::ExpectBinary(p, 0)? },
+    
+    InterpolationExpr, p => {
+        let begin = p.GetPos();
 
-        let name = Ident::Expect(p)?;
+        let mut parts: Vec<Expr> = vec![];
+        loop {
+            let tail = p.At(TokenKind::StringTail);
 
-        p.MatchTerm(TokenKind::LPAREN)?;
+            let tok = p.Bump();
+            parts.push(Expr::LiteralValue(Box::new(LiteralValue { Pos: tok.Pos, Token: tok })));
 
-        let params = match p.Token.Kind {
-            TokenKind::RPAREN => {
-                List { Pos: range![begin, p], Elements: vec![], Delimiter: TokenKind::None, Term: TokenKind::RPAREN }
+            if tail {
+                break;
             }
-            _ => {
-                List::Expect(p, TokenKind::COMMA, TokenKind::RPAREN)?
+
+            parts.push(Expr::Expect(p)?);
+
+            if !p.At(TokenKind::StringMiddle) && !p.At(TokenKind::StringTail) {
+                p.ReportAndRecover(SyntaxError::UnexpectedNode(UnexpectedNodeError { Pos: p.Token.Pos, Want: Node::TokenKind(TokenKind::StringTail), Have: Node::Token(p.Token.clone()) }))?;
+                break;
             }
-        };
+        }
 
-        let typ = match p.Token.Kind {
-            TokenKind::PASS => {
-                p.Scan()?;
-                FuncType {
-                    Params: params,
-                    Result: Type::Expect(p)?,
-                    Pos: range![begin, p],
-                }
+        let pos = range![begin, p];
+        InterpolationExpr {
+            Parts: List { Pos: pos, Elements: parts, Delimiter: TokenKind::None, Term: TokenKind::StringTail },
+            Pos: pos,
+        }
+    },
+
+    IfExpr, p => {
+        let begin = p.GetPos();
+
+        p.MatchTerm(TokenKind::IF)?;
+        let cond = p.WithStructLiteral(false, |p| Expr::Expect(p))?;
+        let then = StmtBlock::Expect(p)?;
+
+        // `else` may start the line after the closing brace
+        if p.At(TokenKind::SEMICOLON) && tag_matches!(&p.Peek(1).Kind, &TokenKind::ELSE) {
+            p.Bump();
+        }
+
+        let elseBegin = p.GetPos();
+        let elseClause = match p.Eat(TokenKind::ELSE) {
+            true => {
+                let body = match p.Token.Kind {
+                    TokenKind::IF => { Stmt::Expr(Box::from(Expr::IfExpr(Box::new(IfExpr::Expect(p)?)))) }
+                    _ => { Stmt::StmtBlock(Box::from(StmtBlock::Expect(p)?)) }
+                };
+                Optional::Some(ElseClause { Body: body, Pos: range![elseBegin, p] })
             }
-            _ => {
-                FuncType {
-                    Params: params,
-                    Result: Type::None,
-                    Pos: range![begin, p],
-                }
+            false => { Optional::None }
+        };
+
+        IfExpr {
+            Cond: cond,
+            Then: then,
+            Else: elseClause,
+            Pos: range![begin, p],
+        }
+    },
+
+    MatchExpr, p => {
+        let begin = p.GetPos();
+
+        p.MatchTerm(TokenKind::MATCH)?;
+        let subject = p.WithStructLiteral(false, |p| Expr::Expect(p))?;
+
+        let armsBegin = p.GetPos();
+        p.MatchTerm(TokenKind::LBRACE)?;
+        // arms end with a comma, or with a newline completed to a semicolon
+        let mut arms: Vec<MatchArm> = vec![];
+        while !p.At(TokenKind::RBRACE) && !p.At(TokenKind::EOF) {
+            arms.push(MatchArm::Expect(p)?);
+            if !p.Eat(TokenKind::COMMA) && !p.Eat(TokenKind::SEMICOLON) {
+                break;
             }
+        }
+        p.MatchTerm(TokenKind::RBRACE)?;
+
+        MatchExpr {
+            Subject: subject,
+            Arms: List { Pos: range![armsBegin, p], Elements: arms, Delimiter: TokenKind::COMMA, Term: TokenKind::RBRACE },
+            Pos: range![begin, p],
+        }
+    },
+
+    MatchArm, p => {
+        let begin = p.GetPos();
+
+        let pattern = Pattern::Expect(p)?;
+
+        let guardBegin = p.GetPos();
+        let guard = match p.Eat(TokenKind::IF) {
+            true => { Optional::Some(Guard { Cond: Expr::Expect(p)?, Pos: range![guardBegin, p] }) }
+            false => { Optional::None }
         };
 
-        FuncDecl {
-            Name: Optional::Some(name),
-            Type: typ,
-            Stmt: Optional::None,
+        p.MatchTerm(TokenKind::ARROW)?;
+
+        MatchArm {
+            Pattern: pattern,
+            Guard: guard,
+            Body: Stmt::Expect(p)?,
             Pos: range![begin, p],
         }
     },
-    
-    Expr, p => {
-        match p.Token.Kind {
-            TokenKind::Ident => { todo!() }
-            TokenKind::Int(_) | TokenKind::Float | TokenKind::Char | TokenKind::String => {
-                Expr::LiteralValue(Box::new(LiteralValue {
-                    Pos: p.Token.Pos,
-                    Token: p.Token.clone(),
+
+    // Alternatives separated by `|`, each a single pattern.
+    Pattern, p => {
+        let begin = p.GetPos();
+
+        let first = Pattern::ExpectSingle(p)?;
+        match p.At(TokenKind::OR) {
+            true => {
+                let mut alternatives = vec![first];
+                while p.Eat(TokenKind::OR) {
+                    alternatives.push(Pattern::ExpectSingle(p)?);
+                }
+                let pos = range![begin, p];
+                Pattern::OrPattern(Box::new(OrPattern {
+                    Alternatives: List { Pos: pos, Elements: alternatives, Delimiter: TokenKind::OR, Term: TokenKind::None },
+                    Pos: pos,
                 }))
             }
-            _ => { todo!() }
+            false => { first }
         }
     },
-    
+
+    LiteralPattern, p => {
+        let begin = p.GetPos();
+
+        LiteralPattern {
+            Value: Expr::ExpectUnary(p)?,
+            Pos: range![begin, p],
+        }
+    },
+
+    FieldPattern, p => {
+        let begin = p.GetPos();
+
+        let name = Ident::Expect(p)?;
+        let pattern = match p.Eat(TokenKind::COLON) {
+            true => { Pattern::Expect(p)? }
+            false => { Pattern::BindingPattern(Box::new(BindingPattern { Pos: name.Pos, Name: Ident { Pos: name.Pos, Token: name.Token.clone() } })) }
+        };
+
+        FieldPattern {
+            Name: name,
+            Pattern: pattern,
+            Pos: range![begin, p],
+        }
+    },
+
+    Path, p => {
+        let begin = p.GetPos();
+
+        let mut segments = vec![Ident::Expect(p)?];
+        while p.Eat(TokenKind::SCOPE) {
+            segments.push(Ident::Expect(p)?);
+        }
+
+        Path {
+            Segments: segments,
+            Pos: range![begin, p],
+        }
+    },
+
     Stmt, p => {
-        let expr = Expr::Expect(p)?;
+        let labeled = p.At(TokenKind::Ident) && tag_matches!(&p.Peek(1).Kind, &TokenKind::COLON);
 
         match p.Token.Kind {
-            TokenKind::MUT => {
-                Stmt::MutDecl(Box::from(MutDecl::Expect(p)?))
-            }
-            TokenKind::VAL => {
-                Stmt::MutDecl(Box::from(MutDecl::Expect(p)?))
+            TokenKind::MUT | TokenKind::VAL => { Stmt::VarDecl(Box::from(VarDecl::Expect(p)?)) }
+            TokenKind::FUNC => { Stmt::FuncDecl(Box::from(FuncDecl::Expect(p)?)) }
+            TokenKind::LBRACE => { Stmt::StmtBlock(Box::from(StmtBlock::Expect(p)?)) }
+            TokenKind::FOR => {
+                match tag_matches!(&p.Peek(1).Kind, &TokenKind::Ident) && tag_matches!(&p.Peek(2).Kind, &TokenKind::COLON) {
+                    true => { Stmt::ForInStmt(Box::from(ForInStmt::Expect(p)?)) }
+                    false => { Stmt::ForStmt(Box::from(ForStmt::Expect(p)?)) }
+                }
             }
+            TokenKind::BREAK | TokenKind::CONTINUE => { Stmt::BranchStmt(Box::from(BranchStmt::Expect(p)?)) }
+            TokenKind::RETURN => { Stmt::ReturnStmt(Box::from(ReturnStmt::Expect(p)?)) }
+            TokenKind::Ident if labeled => { Stmt::LabeledStmt(Box::from(LabeledStmt::Expect(p)?)) }
             _ => {
-                Stmt::Expr(Box::from(Expr::Expect(p)?))
+                let begin = p.GetPos();
+                let x = Expr::Expect(p)?;
+                match p.Token.Kind {
+                    TokenKind::ASSIGN |
+                    TokenKind::ADD_ASSIGN | TokenKind::SUB_ASSIGN | TokenKind::MUL_ASSIGN | TokenKind::QUO_ASSIGN | TokenKind::REM_ASSIGN |
+                    TokenKind::AND_ASSIGN | TokenKind::OR_ASSIGN | TokenKind::XOR_ASSIGN | TokenKind::SHL_ASSIGN | TokenKind::SHR_ASSIGN => {
+                        let op = p.Bump().Kind;
+                        Stmt::AssignStmt(Box::from(AssignStmt {
+                            Lhs: x,
+                            Op: op,
+                            Rhs: Expr::Expect(p)?,
+                            Pos: range![begin, p],
+                        }))
+                    }
+                    _ => { Stmt::Expr(Box::from(x)) }
+                }
             }
         }
     },
-    
-    MutDecl, p => {
+
+    LabeledStmt, p => {
         let begin = p.GetPos();
 
-        p.MatchTerm(TokenKind::MUT)?;
+        let label = Ident::Expect(p)?;
+        p.MatchTerm(TokenKind::COLON)?;
 
-        MutDecl {
-            Name: Ident::Expect(p)?,
-            Type: Type::Expect(p)?,
+        LabeledStmt {
+            Label: label,
+            Stmt: Stmt::Expect(p)?,
+            Pos: range![begin, p],
+        }
+    },
+
+    ForStmt, p => {
+        let begin = p.GetPos();
+
+        p.MatchTerm(TokenKind::FOR)?;
+        let cond = match p.At(TokenKind::LBRACE) {
+            true => { Expr::None }
+            false => { p.WithStructLiteral(false, |p| Expr::Expect(p))? }
+        };
+
+        ForStmt {
+            Cond: cond,
+            Body: StmtBlock::Expect(p)?,
+            Pos: range![begin, p],
+        }
+    },
+
+    ForInStmt, p => {
+        let begin = p.GetPos();
+
+        p.MatchTerm(TokenKind::FOR)?;
+        let name = Ident::Expect(p)?;
+        p.MatchTerm(TokenKind::COLON)?;
+
+        ForInStmt {
+            Name: name,
+            Iter: p.WithStructLiteral(false, |p| Expr::Expect(p))?,
+            Body: StmtBlock::Expect(p)?,
+            Pos: range![begin, p],
+        }
+    },
+
+    BranchStmt, p => {
+        let begin = p.GetPos();
+
+        let tok = p.Bump();
+        let label = match p.At(TokenKind::Ident) {
+            true => { Optional::Some(Ident::Expect(p)?) }
+            false => { Optional::None }
+        };
+
+        BranchStmt {
+            Tok: tok.Kind,
+            Label: label,
+            Pos: range![begin, p],
+        }
+    },
+
+    ReturnStmt, p => {
+        let begin = p.GetPos();
+
+        p.MatchTerm(TokenKind::RETURN)?;
+        let result = match p.Token.Kind {
+            TokenKind::SEMICOLON | TokenKind::RBRACE | TokenKind::EOF => { Expr::None }
+            _ => { Expr::Expect(p)? }
+        };
+
+        ReturnStmt {
+            Result: result,
             Pos: range![begin, p],
         }
     },
     
+    VarDecl, p => {
+        let begin = p.GetPos();
+
+        let mut keyword = match p.Token.Kind {
+            TokenKind::VAL | TokenKind::MUT => { p.Bump().Kind }
+            _ => { p.MatchTerm(TokenKind::VAL)?.Kind }
+        };
+        // `let mut x` is the same as `mut x`
+        if tag_matches!(&keyword, &TokenKind::VAL) && p.Eat(TokenKind::MUT) {
+            keyword = TokenKind::MUT;
+        }
+        let mutable = tag_matches!(&keyword, &TokenKind::MUT);
+
+        // a lone name before `(` binds a tuple-typed value, `let t (int, string)`; a union pattern here takes a path,
+        // `let Shape::Circle(r) = s`
+        let tupleTyped = p.At(TokenKind::Ident) && p.Token.Literal.as_ref() != "_" && tag_matches!(&p.Peek(1).Kind, &TokenKind::LPAREN);
+        let pattern = match tupleTyped {
+            true => {
+                let name = Ident::Expect(p)?;
+                Pattern::BindingPattern(Box::new(BindingPattern { Pos: name.Pos, Name: name }))
+            }
+            false => { Pattern::Expect(p)? }
+        };
+        let typ = match p.Token.Kind {
+            TokenKind::ASSIGN | TokenKind::SEMICOLON | TokenKind::RBRACE | TokenKind::EOF => { Type::None }
+            _ => { Type::Expect(p)? }
+        };
+        let value = match p.Eat(TokenKind::ASSIGN) {
+            true => { Expr::Expect(p)? }
+            false => { Expr::None }
+        };
+
+        // a destructuring pattern takes its bindings from the value, a single binding may be initialized later if typed
+        let pos = range![begin, p];
+        if matches!(value, Expr::None) && (matches!(typ, Type::None) || !matches!(pattern, Pattern::BindingPattern(_))) {
+            p.Report(SyntaxError::MissingInitializer(MissingInitializerError { Pos: pos }));
+        }
+
+        VarDecl {
+            Keyword: keyword,
+            Mutable: mutable,
+            Pattern: pattern,
+            Type: typ,
+            Value: value,
+            Pos: pos,
+        }
+    },
+    
     StmtBlock, p => {
         let begin = p.GetPos();
 
+        p.MatchTerm(TokenKind::LBRACE)?;
+        let stmtList = p.WithStructLiteral(true, |p| List::Expect(p, TokenKind::SEMICOLON, TokenKind::RBRACE))?;
+        p.MatchTerm(TokenKind::RBRACE)?;
+
         StmtBlock {
-            StmtList: List::Expect(p, TokenKind::SEMICOLON, TokenKind::None)?,
-            Type: Type::None, // TODO
+            StmtList: stmtList,
+            Type: Type::None,
             Pos: range![begin, p],
         }
     }
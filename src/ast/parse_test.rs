@@ -2,27 +2,636 @@
 // This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
-use crate::parser::{Parser, ParserError};
+use crate::parser::{Parser, ParserError, SyntaxError};
 
 #[test]
 fn TestParser_ExpectFuncDecl() {
-    fn test() -> Result<(), ParserError> {
-        let mut p = Parser::new(String::from(r#"
-        fun Function() {
-            return
+    use crate::ast::*;
+    use crate::parser::AstNodeParserTrait;
+
+    let mut p = Parser::new(r#"
+        func Function(a, b int) <- int {
+            let c = {
+                let d = a * b
+                d + 1
+            }
+            return c
+        }
+        func External(a int)
+        "#);
+    p.Bump();
+
+    let f = FuncDecl::Expect(&mut p).unwrap();
+    let body = match &f.Stmt {
+        Optional::Some(body) => { body }
+        Optional::None => { panic!() }
+    };
+    assert_eq!(body.StmtList.Elements.len(), 2);
+    assert!(body.Value().is_none());
+    match &body.StmtList.Elements[0] {
+        Stmt::VarDecl(decl) => {
+            match &decl.Value {
+                Expr::StmtBlock(block) => { assert_eq!(block.Value().unwrap().to_string(), "(d + 1)"); }
+                _ => { panic!() }
+            }
+        }
+        _ => { panic!() }
+    }
+
+    assert!(p.Eat(TokenKind::SEMICOLON));
+    let f = FuncDecl::Expect(&mut p).unwrap();
+    assert!(matches!(f.Stmt, Optional::None));
+    assert!(p.SyntaxErrors.is_empty());
+}
+
+#[test]
+fn TestParser_ExpectInterpolationExpr() {
+    use crate::ast::*;
+    use crate::parser::AstNodeParserTrait;
+
+    let mut p = Parser::new(r#""a {1} b {"c"}!" ;"#);
+    p.Scan().unwrap();
+
+    match Expr::Expect(&mut p).unwrap() {
+        Expr::InterpolationExpr(e) => {
+            assert_eq!(e.Parts.Elements.len(), 5);
+            let literals = e.Parts.Elements.iter().map(|e| match e {
+                Expr::LiteralValue(v) => { v.Token.Literal.to_string() }
+                _ => { panic!() }
+            }).collect::<Vec<_>>();
+            assert_eq!(literals, vec!["a ", "1", " b ", "c", "!"]);
+            assert_eq!(e.to_string(), "\"a {1} b {c}!\"");
+        }
+        _ => { panic!() }
+    }
+    assert!(matches!(p.Token.Kind, TokenKind::SEMICOLON));
+
+    // newlines inside `{}` do not end the statement
+    let mut p = Parser::new("\"a{\nx\n}b\"\n");
+    p.Scan().unwrap();
+    match Expr::Expect(&mut p).unwrap() {
+        Expr::InterpolationExpr(e) => { assert_eq!(e.Parts.Elements.len(), 3); }
+        _ => { panic!() }
+    }
+    assert!(matches!(p.Token.Kind, TokenKind::SEMICOLON));
+    assert!(p.SyntaxErrors.is_empty());
+}
+
+#[test]
+fn TestParser_ExpectStructType() {
+    use crate::ast::*;
+    use crate::parser::AstNodeParserTrait;
+
+    let mut p = Parser::new("struct Point {\n    X, Y int\n    Next Point\n}\nfunc Len(p Point) <- int\n");
+    p.Bump();
+
+    let s = StructType::Expect(&mut p).unwrap();
+    assert_eq!(s.Name.Token.Literal.as_ref(), "Point");
+    assert_eq!(s.FieldList.Elements.len(), 2);
+    assert_eq!(s.FieldList.Elements[0].Names.Elements.len(), 2);
+    assert_eq!(s.Pos.End.Line, 3);
+
+    assert!(p.Eat(TokenKind::SEMICOLON));
+    let f = FuncDecl::Expect(&mut p).unwrap();
+    assert!(matches!(f.Type.Result, Type::Ident(_)));
+    assert_eq!(f.Type.Params.Elements.len(), 1);
+    assert!(p.SyntaxErrors.is_empty());
+}
+
+#[test]
+fn TestCst_RoundTrip() {
+    use crate::ast::*;
+    use crate::scanner::{Trivia, TriviaKind};
+
+    for src in [
+        "func Add(a, b int) <- int {\n\treturn a + b // sum\n}\n",
+        "/// doc\nstruct S { /* nested /* c */ */ X int }\r\n\n",
+        "  let s = \"a {b} c\" + r#\"raw\"# + 'x'\t\n",
+        "func (a, b] { ) } €€ \"unterminated\n1.e 0x1.p '' /* open",
+        "",
+    ] {
+        let cst = Cst::Parse(src);
+        assert_eq!(cst.to_string(), src);
+    }
+
+    let cst = Cst::Parse("f(a, [b]) // c\n]");
+    let kinds = cst.Root.Children.iter().filter_map(|e| match e {
+        CstElement::Node(node) => { Some(node.Kind.clone()) }
+        CstElement::Token(_) => { None }
+    }).collect::<Vec<_>>();
+    assert_eq!(kinds, vec![CstKind::Group, CstKind::Error]);
+    match &cst.Root.Children[1] {
+        CstElement::Node(node) => {
+            match node.Children.last() {
+                Some(CstElement::Token(tok)) => {
+                    assert_eq!(tok.Text, ")");
+                    assert!(matches!(tok.Trailing[..], [Trivia { Kind: TriviaKind::Whitespace, .. }, Trivia { Kind: TriviaKind::Comment, .. }]));
+                }
+                _ => { panic!() }
+            }
+        }
+        _ => { panic!() }
+    }
+}
+
+#[test]
+fn TestCst_GrammarNodes() {
+    use crate::ast::*;
+
+    // grammar nodes by kind, groups as `[]`, tokens by text
+    fn Shape(node: &CstNode) -> String {
+        let children = node.Children.iter().map(|e| match e {
+            CstElement::Node(node) => { Shape(node) }
+            CstElement::Token(tok) => { tok.Text.to_string() }
+        }).collect::<Vec<_>>().join(" ");
+        match &node.Kind {
+            CstKind::Node(kind) => { format!("{}({})", kind, children) }
+            CstKind::Group => { format!("[{}]", children) }
+            _ => { children }
+        }
+    }
+
+    let src = "func Add(a, b int) <- int {\n\treturn a + b // sum\n}\nstruct S { X int }\n";
+    let cst = Cst::Parse(src);
+    assert_eq!(cst.to_string(), src);
+    assert_eq!(Shape(&cst.Root), concat!(
+        "FuncDecl(func Ident(Add) FuncType([( Field(Ident(a) , Ident(b) Ident(int)) )] <- Ident(int)) ",
+        "StmtBlock([{ \n ReturnStmt(return BinaryExpr(Ident(a) + Ident(b))) \n }])) \n ",
+        "StructType(struct Ident(S) [{ Field(Ident(X) Ident(int)) }]) \n",
+    ));
+}
+
+#[test]
+fn TestParser_ExpectExpr() {
+    use crate::ast::*;
+    use crate::parser::AstNodeParserTrait;
+
+    for (src, want) in [
+        ("a + b * c", "(a + (b * c))"),
+        ("a - b - c", "((a - b) - c)"),
+        ("a || b && c == d", "(a || (b && (c == d)))"),
+        ("a | b ^ c & d << 1", "(a | (b ^ (c & (d << 1))))"),
+        ("-a * !b", "(-a * !b)"),
+        ("(a + b) * c", "(((a + b)) * c)"),
+        ("f(a, b + 1)?.X[0]", "f(a, (b + 1))?.X[0]"),
+        ("&x.Y < ~z", "(&x.Y < ~z)"),
+    ] {
+        let mut p = Parser::new(src);
+        p.Bump();
+
+        let e = Expr::Expect(&mut p).unwrap();
+        assert_eq!(e.to_string(), want);
+        assert!(matches!(p.Token.Kind, TokenKind::EOF | TokenKind::SEMICOLON));
+        assert!(p.SyntaxErrors.is_empty());
+    }
+
+    let mut p = Parser::new("a + )");
+    p.Bump();
+    Expr::Expect(&mut p).unwrap();
+    assert_eq!(p.SyntaxErrors.len(), 1);
+    assert!(p.At(TokenKind::RPAREN));
+}
+
+#[test]
+fn TestParser_ExpectControlFlow() {
+    use crate::ast::*;
+    use crate::parser::AstNodeParserTrait;
+
+    let mut p = Parser::new(r#"{
+        outer: for i : 0..n {
+            for {
+                if i == 0 {
+                    continue outer
+                } else if i > 9 {
+                    break
+                }
+                else {
+                    return i * 2
+                }
+            }
         }
-        "#).chars().collect());
+        for a < b { return }
+        let x int
+    }"#);
+    p.Bump();
+
+    let block = StmtBlock::Expect(&mut p).unwrap();
+    assert!(p.SyntaxErrors.is_empty());
+    assert_eq!(block.StmtList.Elements.len(), 3);
 
-        loop {
-            p.Scan()?;
-            println!("{}", p.Token);
+    let labeled = match &block.StmtList.Elements[0] {
+        Stmt::LabeledStmt(it) => { it }
+        _ => { panic!() }
+    };
+    assert_eq!(labeled.Label.Token.Literal.as_ref(), "outer");
+    let forIn = match &labeled.Stmt {
+        Stmt::ForInStmt(it) => { it }
+        _ => { panic!() }
+    };
+    assert_eq!(forIn.Iter.to_string(), "(0 .. n)");
+
+    let forever = match &forIn.Body.StmtList.Elements[0] {
+        Stmt::ForStmt(it) => { it }
+        _ => { panic!() }
+    };
+    assert!(matches!(forever.Cond, Expr::None));
+
+    let ifExpr = match &forever.Body.StmtList.Elements[0] {
+        Stmt::Expr(e) => { match e.as_ref() { Expr::IfExpr(it) => { it } _ => { panic!() } } }
+        _ => { panic!() }
+    };
+    assert_eq!(ifExpr.Cond.to_string(), "(i == 0)");
+    match &ifExpr.Then.StmtList.Elements[0] {
+        Stmt::BranchStmt(it) => {
+            assert!(matches!(it.Tok, TokenKind::CONTINUE));
+            assert!(matches!(&it.Label, Optional::Some(label) if label.Token.Literal.as_ref() == "outer"));
+        }
+        _ => { panic!() }
+    }
+    let elseIf = match &ifExpr.Else {
+        Optional::Some(ElseClause { Body: Stmt::Expr(e), .. }) => { match e.as_ref() { Expr::IfExpr(it) => { it } _ => { panic!() } } }
+        _ => { panic!() }
+    };
+    match &elseIf.Else {
+        Optional::Some(ElseClause { Body: Stmt::StmtBlock(b), .. }) => {
+            assert!(matches!(&b.StmtList.Elements[0], Stmt::ReturnStmt(r) if r.Result.to_string() == "(i * 2)"));
         }
+        _ => { panic!() }
     }
 
-    match test() {
-        Ok(_) => {}
-        Err(e) => {
-            println!("{:?}", e)
+    match &block.StmtList.Elements[1] {
+        Stmt::ForStmt(it) => {
+            assert_eq!(it.Cond.to_string(), "(a < b)");
+            assert!(matches!(&it.Body.StmtList.Elements[0], Stmt::ReturnStmt(r) if matches!(r.Result, Expr::None)));
         }
+        _ => { panic!() }
     }
 }
+
+#[test]
+fn TestParser_ExpectAssignStmt() {
+    use crate::ast::*;
+    use crate::parser::AstNodeParserTrait;
+
+    let mut p = Parser::new(r#"{
+        x = 2
+        a.b[i] += 1
+        let y int
+        y = x >> 1
+        y <<= 2
+    }"#);
+    p.Bump();
+
+    let block = StmtBlock::Expect(&mut p).unwrap();
+    assert!(p.SyntaxErrors.is_empty());
+    let stmts = block.StmtList.Elements.iter().map(|it| match it {
+        Stmt::AssignStmt(it) => { it.to_string() }
+        Stmt::VarDecl(_) => { String::from("let") }
+        _ => { panic!() }
+    }).collect::<Vec<_>>();
+    assert_eq!(stmts, vec!["x = 2", "a.b[i] += 1", "let", "y = (x >> 1)", "y <<= 2"]);
+    assert!(block.Value().is_none());
+}
+
+#[test]
+fn TestParser_ExpectMatchExpr() {
+    use crate::ast::*;
+    use crate::parser::AstNodeParserTrait;
+
+    let mut p = Parser::new(r#"match shape {
+        Shape::Circle(r) if r > 0 => r * r,
+        Shape::Rect { W, H: 0 } => 0
+        Point { X, Y } | Origin => { X }
+        (a, _) => a
+        -9..'z' => 1, 0 => 2
+        Option::None => 3
+        x => return x
+    }"#);
+    p.Bump();
+
+    let e = match Expr::Expect(&mut p).unwrap() {
+        Expr::MatchExpr(e) => { e }
+        _ => { panic!() }
+    };
+    assert!(p.SyntaxErrors.is_empty());
+    assert_eq!(e.Subject.to_string(), "shape");
+
+    let patterns = e.Arms.Elements.iter().map(|arm| arm.Pattern.to_string()).collect::<Vec<_>>();
+    assert_eq!(patterns, vec![
+        "Shape::Circle(r)",
+        "Shape::Rect {W: W, H: 0}",
+        "Point {X: X, Y: Y} | Origin",
+        "(a, _)",
+        "-9..z",
+        "0",
+        "Option::None",
+        "x",
+    ]);
+
+    let arms = &e.Arms.Elements;
+    assert!(matches!(&arms[0].Guard, Optional::Some(g) if g.Cond.to_string() == "(r > 0)"));
+    assert!(matches!(&arms[0].Pattern, Pattern::UnionPattern(_)));
+    assert!(matches!(&arms[1].Pattern, Pattern::StructPattern(_)));
+    assert!(matches!(&arms[2].Pattern, Pattern::OrPattern(_)));
+    assert!(matches!(&arms[2].Body, Stmt::StmtBlock(_)));
+    assert!(matches!(&arms[3].Pattern, Pattern::TuplePattern(_)));
+    assert!(matches!(&arms[4].Pattern, Pattern::RangePattern(_)));
+    assert!(matches!(&arms[5].Pattern, Pattern::LiteralPattern(_)));
+    assert!(matches!(&arms[7].Pattern, Pattern::BindingPattern(_)));
+    assert!(matches!(&arms[7].Body, Stmt::ReturnStmt(_)));
+}
+
+#[test]
+fn TestParser_ExpectUnionType() {
+    use crate::ast::*;
+    use crate::parser::AstNodeParserTrait;
+
+    let mut p = Parser::new("union Shape {\n    Circle(R float)\n    /// Width and height\n    Rect(W, H float)\n    Empty\n}");
+    p.Bump();
+
+    let u = match Type::Expect(&mut p).unwrap() {
+        Type::UnionType(u) => { u }
+        _ => { panic!() }
+    };
+    assert!(p.SyntaxErrors.is_empty());
+    assert_eq!(u.Name.Token.Literal.as_ref(), "Shape");
+
+    let variants = &u.VariantList.Elements;
+    assert_eq!(variants.len(), 3);
+    assert_eq!(variants[0].FieldList.Elements.len(), 1);
+    assert_eq!(variants[1].FieldList.Elements[0].Names.Elements.len(), 2);
+    assert!(matches!(&variants[1].Doc, Optional::Some(doc) if doc.Text.contains("Width and height")));
+    assert!(variants[2].FieldList.Elements.is_empty());
+}
+
+#[test]
+fn TestParser_ExpectVarDecl() {
+    use crate::ast::*;
+    use crate::parser::AstNodeParserTrait;
+
+    let mut p = Parser::new("{\n    let x = 1\n    mut n int\n    let (a, b) Pair = Swap(pair)\n    let Point { X, Y } = p\n}");
+    p.Bump();
+
+    let block = StmtBlock::Expect(&mut p).unwrap();
+    assert!(p.SyntaxErrors.is_empty());
+
+    let decls = block.StmtList.Elements.iter().map(|it| match it {
+        Stmt::VarDecl(it) => { it }
+        _ => { panic!() }
+    }).collect::<Vec<_>>();
+    assert_eq!(decls.len(), 4);
+
+    assert!(!decls[0].Mutable);
+    assert!(matches!(decls[0].Type, Type::None));
+    assert_eq!(decls[0].Value.to_string(), "1");
+
+    assert!(decls[1].Mutable);
+    assert!(matches!(&decls[1].Type, Type::Ident(it) if it.Token.Literal.as_ref() == "int"));
+    assert!(matches!(decls[1].Value, Expr::None));
+
+    assert!(matches!(decls[2].Pattern, Pattern::TuplePattern(_)));
+    assert_eq!(decls[2].Value.to_string(), "Swap(pair)");
+    assert!(matches!(decls[3].Pattern, Pattern::StructPattern(_)));
+
+    let mut p = Parser::new("{\n    let mut x int\n    x = 1\n}");
+    p.Bump();
+    let block = StmtBlock::Expect(&mut p).unwrap();
+    assert!(p.SyntaxErrors.is_empty());
+    assert!(matches!(&block.StmtList.Elements[0], Stmt::VarDecl(it) if it.Mutable && matches!(it.Value, Expr::None)));
+    assert!(matches!(&block.StmtList.Elements[1], Stmt::AssignStmt(it) if it.to_string() == "x = 1"));
+
+    // a lone name before `(` is typed with a tuple, a union pattern there takes a path
+    let mut p = Parser::new("{\n    let t (int, string) = x\n    mut u (int, string)\n    let Shape::Circle(r) = s\n}");
+    p.Bump();
+    let block = StmtBlock::Expect(&mut p).unwrap();
+    assert!(p.SyntaxErrors.is_empty());
+    let decls = block.StmtList.Elements.iter().map(|it| match it {
+        Stmt::VarDecl(it) => { it }
+        _ => { panic!() }
+    }).collect::<Vec<_>>();
+    for decl in &decls[..2] {
+        assert!(matches!(decl.Pattern, Pattern::BindingPattern(_)));
+        assert!(matches!(&decl.Type, Type::TupleType(t) if t.to_string() == "(int, string)"));
+    }
+    assert!(matches!(decls[1].Value, Expr::None));
+    assert!(matches!(&decls[2].Pattern, Pattern::UnionPattern(it) if it.to_string() == "Shape::Circle(r)"));
+    assert!(matches!(decls[2].Type, Type::None));
+
+    for src in ["let x", "mut (a, b) Pair"] {
+        let mut p = Parser::new(src);
+        p.Bump();
+        VarDecl::Expect(&mut p).unwrap();
+        assert!(matches!(p.SyntaxErrors.as_slice(), [SyntaxError::MissingInitializer(_)]));
+    }
+}
+
+#[test]
+fn TestParser_ExpectImplDecl() {
+    use crate::ast::*;
+    use crate::parser::AstNodeParserTrait;
+
+    let mut p = Parser::new(r#"trait Stream {
+        /// Reads into buf
+        Read(&mut self, buf Bytes) <- int
+        func Len(&self) <- int
+        Close(self)
+    }
+    impl Stream for File {
+        func Len(&self) <- int { self.Size }
+        func Close(self) {}
+    }"#);
+    p.Bump();
+
+    let t = TraitType::Expect(&mut p).unwrap();
+    let methods = &t.FuncList.Elements;
+    assert_eq!(methods.len(), 3);
+    assert!(matches!(&methods[0].Doc, Optional::Some(_)));
+    assert!(matches!(&methods[0].Type.Receiver, Optional::Some(Receiver { Borrow: true, Mutable: true, .. })));
+    assert_eq!(methods[0].Type.Params.Elements.len(), 1);
+    assert!(methods[0].Type.to_string().starts_with("fun (&mut self, buf"));
+    assert!(matches!(&methods[1].Type.Receiver, Optional::Some(Receiver { Borrow: true, Mutable: false, .. })));
+    assert!(methods[1].Type.Params.Elements.is_empty());
+    assert_eq!(methods[1].Type.to_string(), "fun (&self) int");
+    assert!(matches!(&methods[2].Type.Receiver, Optional::Some(Receiver { Borrow: false, .. })));
+    assert!(methods.iter().all(|it| matches!(it.Stmt, Optional::None)));
+
+    assert!(p.Eat(TokenKind::SEMICOLON));
+    let i = ImplDecl::Expect(&mut p).unwrap();
+    assert_eq!(i.Trait.to_string(), "Stream");
+    assert_eq!(i.Type.to_string(), "File");
+    assert_eq!(i.FuncList.Elements.len(), 2);
+    assert!(matches!(&i.FuncList.Elements[0].Stmt, Optional::Some(body) if body.Value().unwrap().to_string() == "self.Size"));
+    assert!(p.SyntaxErrors.is_empty());
+}
+
+#[test]
+fn TestParser_ExpectGenerics() {
+    use crate::ast::*;
+    use crate::parser::AstNodeParserTrait;
+
+    let mut p = Parser::new(r#"func Max<T: Ord + Copy>(a, b T) <- T where T: Default {
+        let m Map<K, List<V>>= New<Map<K, List<V>>>()
+        a < b > c
+        a < b > (c)
+        Max<int>(a, b) >> 1
+        f(a < 1, b > (c))
+    }"#);
+    p.Bump();
+
+    let f = FuncDecl::Expect(&mut p).unwrap();
+    assert!(p.SyntaxErrors.is_empty());
+    assert_eq!(f.TypeParams.to_string(), "T: Ord + Copy");
+    assert_eq!(f.Where.to_string(), "T: Default");
+
+    let stmts = match &f.Stmt {
+        Optional::Some(body) => { &body.StmtList.Elements }
+        Optional::None => { panic!() }
+    };
+    match &stmts[0] {
+        Stmt::VarDecl(decl) => {
+            assert!(matches!(&decl.Type, Type::GenericType(t) if t.to_string() == "Map<K, List<V>>"));
+            assert_eq!(decl.Value.to_string(), "New<Map<K, List<V>>>()");
+        }
+        _ => { panic!() }
+    }
+    assert_eq!(stmts[1].to_string(), "((a < b) > c)");
+    assert_eq!(stmts[2].to_string(), "a<b>(c)"); // a `(` after the matching `>` makes type arguments
+    assert_eq!(stmts[3].to_string(), "(Max<int>(a, b) >> 1)");
+    assert_eq!(stmts[4].to_string(), "f((a < 1), (b > (c)))"); // a literal never starts type arguments
+
+    for (src, params) in [
+        ("struct List<T> where T: Copy {\n}", 1),
+        ("union Option<T> {\n    Some(Value T)\n    None\n}", 1),
+        ("trait Iter<T, U: Eq> {\n    Next(&mut self) <- Option<T>\n}", 2),
+        ("impl<T> Iter<T> for List<T> {\n}", 1),
+    ] {
+        let mut p = Parser::new(src);
+        p.Bump();
+        let typeParams = match &p.Token.Kind {
+            TokenKind::IMPL => { ImplDecl::Expect(&mut p).unwrap().TypeParams }
+            _ => match Type::Expect(&mut p).unwrap() {
+                Type::StructType(t) => { t.TypeParams }
+                Type::UnionType(t) => { t.TypeParams }
+                Type::TraitType(t) => { t.TypeParams }
+                _ => { panic!() }
+            }
+        };
+        assert_eq!(typeParams.Elements.len(), params, "{}", src);
+        assert!(p.SyntaxErrors.is_empty(), "{}", src);
+    }
+
+    // a newline after the `>` closing type arguments ends the field, method or statement
+    let mut p = Parser::new("struct List<T> {\n    Items List<List<T>>\n    N int\n}");
+    p.Bump();
+    match Type::Expect(&mut p).unwrap() {
+        Type::StructType(t) => {
+            assert_eq!(t.FieldList.to_string(), "Items List<List<T>>; N int");
+        }
+        _ => { panic!() }
+    }
+    assert!(p.SyntaxErrors.is_empty());
+
+    let mut p = Parser::new("trait Get<T> {\n    Get(&self) <- Option<T>\n    Len(&self) <- int\n}");
+    p.Bump();
+    match Type::Expect(&mut p).unwrap() {
+        Type::TraitType(t) => { assert_eq!(t.FuncList.Elements.len(), 2) }
+        _ => { panic!() }
+    }
+    assert!(p.SyntaxErrors.is_empty());
+
+    let mut p = Parser::new("{\n    mut m List<T>\n    m = x\n}");
+    p.Bump();
+    let block = StmtBlock::Expect(&mut p).unwrap();
+    assert!(p.SyntaxErrors.is_empty());
+    assert_eq!(block.StmtList.Elements.len(), 2);
+    assert!(matches!(&block.StmtList.Elements[0], Stmt::VarDecl(decl) if decl.Type.to_string() == "List<T>"));
+}
+
+#[test]
+fn TestParser_ExpectCompositeType() {
+    use crate::ast::*;
+    use crate::parser::AstNodeParserTrait;
+
+    for src in ["[]int", "[4]u8", "[N * 2][]?T", "(int, string)", "()", "?&T", "&mut List<T>", "&&mut T"] {
+        let mut p = Parser::new(src);
+        p.Bump();
+        let t = Type::Expect(&mut p).unwrap();
+        assert!(p.SyntaxErrors.is_empty(), "{}", src);
+        assert!(matches!(p.Token.Kind, TokenKind::EOF | TokenKind::SEMICOLON), "{}", src);
+        match src {
+            "[N * 2][]?T" => { assert_eq!(t.to_string(), "[(N * 2)][]?T"); }
+            "&&mut T" => { assert_eq!(t.to_string(), "&&mut T"); }
+            _ => { assert_eq!(t.to_string(), src); }
+        }
+    }
+
+    for (src, want) in [
+        ("[1, 2, 3]", "[1, 2, 3]"),
+        ("(a, b)", "(a, b)"),
+        ("(a,)", "(a,)"),
+        ("()", "()"),
+        ("(a)", "(a)"),
+        ("&mut x[0] + &&y", "(&mut x[0] + &&y)"),
+        ("t.0 + t.1.X", "(t.0 + t.1.X)"),
+        ("t.0.1", "t.0.1"),
+        ("t.0.Elem + t.1.e", "(t.0.Elem + t.1.e)"),
+    ] {
+        let mut p = Parser::new(src);
+        p.Bump();
+        assert_eq!(Expr::Expect(&mut p).unwrap().to_string(), want);
+        assert!(p.SyntaxErrors.is_empty(), "{}", src);
+    }
+
+    let mut p = Parser::new("(a,)");
+    p.Bump();
+    assert!(matches!(Expr::Expect(&mut p).unwrap(), Expr::TupleExpr(t) if t.Elements.Elements.len() == 1));
+
+    // `0.1` scans as one float, split back into two indexes
+    let mut p = Parser::new("t.0.1");
+    p.Bump();
+    match Expr::Expect(&mut p).unwrap() {
+        Expr::TupleIndexExpr(outer) => {
+            assert_eq!((outer.Index.Literal.as_ref(), outer.Index.Pos.Begin.Column, outer.Pos.End.Column), ("1", 4, 5));
+            assert!(matches!(&outer.Expr, Expr::TupleIndexExpr(inner) if inner.Index.Literal.as_ref() == "0" && inner.Pos.End.Column == 3));
+        }
+        _ => { panic!() }
+    }
+
+    for src in ["t.0x1", "t.1u8", "t.1e5"] {
+        let mut p = Parser::new(src);
+        p.Bump();
+        Expr::Expect(&mut p).unwrap();
+        assert_eq!(p.SyntaxErrors.len(), 1, "{}", src);
+    }
+}
+
+#[test]
+fn TestParser_ExpectStructLit() {
+    use crate::ast::*;
+    use crate::parser::AstNodeParserTrait;
+
+    for (src, want) in [
+        ("Point{X: 1, Y: 2}", "Point{X: 1, Y: 2}"),
+        ("geo.Point{X, Y: y + 1}.X", "geo.Point{X: X, Y: (y + 1)}.X"),
+        ("Point{X: 1, ..origin}", "Point{X: 1, ..origin}"),
+        ("Point{..origin}", "Point{..origin}"),
+        ("Line{\n    A: Point{},\n    B: b\n}", "Line{A: Point{}, B: b}"),
+    ] {
+        let mut p = Parser::new(src);
+        p.Bump();
+        assert_eq!(Expr::Expect(&mut p).unwrap().to_string(), want);
+        assert!(p.SyntaxErrors.is_empty(), "{}", src);
+    }
+
+    // `{` after a name in a header starts the body, unless the name is parenthesized
+    let mut p = Parser::new("{\n    if p == origin { p.X }\n    for q == (Point{X: 1}) { }\n    match p { _ => Point{X: 0} }\n}");
+    p.Bump();
+    let block = StmtBlock::Expect(&mut p).unwrap();
+    assert!(p.SyntaxErrors.is_empty());
+    let stmts = block.StmtList.Elements.iter().map(|it| it.to_string()).collect::<Vec<_>>();
+    assert_eq!(stmts, vec![
+        "if (p == origin) {p.X}",
+        "for (q == (Point{X: 1})) {}",
+        "match p {_ => Point{X: 0}}",
+    ]);
+}
@@ -3,6 +3,7 @@
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
 use std::fmt::{Display, Formatter};
+use std::rc::Rc;
 
 use crate::scanner::*;
 
@@ -11,12 +12,18 @@ macro_rules! def_tokens {
         #[derive(Clone, Debug)]
         pub enum $typ_name {
             None,
+            EOF,
             Ident,
             Operator,
             Int(crate::scanner::IntFormat),
-            Float,
+            Float(crate::scanner::IntFormat),
             String,
+            StringHead,
+            StringMiddle,
+            StringTail,
             Char,
+            Comment,
+            Invalid,
             $($name,)*
         }
 
@@ -43,6 +50,14 @@ macro_rules! def_tokens {
                     )*
                 ])
             }
+
+            // Punctuation tokens, longest first, for the scanner to split operators by maximal munch.
+            pub fn Operators() -> Vec<&'static str> {
+                let mut operators: Vec<&'static str> = vec![$($literal,)*];
+                operators.retain(|it| it.chars().all(|ch| ch.is_ascii_punctuation()));
+                operators.sort_by(|a, b| b.len().cmp(&a.len()));
+                operators
+            }
         }
     };
 }
@@ -52,18 +67,23 @@ def_tokens! {
         PASS        "<-",
 
         BREAK       "break",
+        CONST       "const",
         CONTINUE    "continue",
         ELSE        "else",
         FOR         "for",
         FUNC        "func",
         IF          "if",
+        IMPL        "impl",
         IMPORT      "import",
         TRAIT       "trait",
         RETURN      "return",
         MATCH       "match",
+        PACKAGE     "package",
         STRUCT      "struct",
+        UNION       "union",
         MUT         "mut",
         VAL         "let",
+        WHERE       "where",
 
         LPAREN      "(",
         LBRACK      "[",
@@ -76,20 +96,92 @@ def_tokens! {
         SEMICOLON   ";",
         COMMA       ",",
         DOT         ".",
+
+        ADD         "+",
+        SUB         "-",
+        MUL         "*",
+        QUO         "/",
+        REM         "%",
+
+        AND         "&",
+        OR          "|",
+        XOR         "^",
+        SHL         "<<",
+        SHR         ">>",
+        TILDE       "~",
+
+        ADD_ASSIGN  "+=",
+        SUB_ASSIGN  "-=",
+        MUL_ASSIGN  "*=",
+        QUO_ASSIGN  "/=",
+        REM_ASSIGN  "%=",
+        AND_ASSIGN  "&=",
+        OR_ASSIGN   "|=",
+        XOR_ASSIGN  "^=",
+        SHL_ASSIGN  "<<=",
+        SHR_ASSIGN  ">>=",
+
+        LAND        "&&",
+        LOR         "||",
+        NOT         "!",
+
+        EQL         "==",
+        NEQ         "!=",
+        LSS         "<",
+        GTR         ">",
+        LEQ         "<=",
+        GEQ         ">=",
+
+        ASSIGN      "=",
+        QUESTION    "?",
+        RANGE       "..",
+        ARROW       "=>",
+        SCOPE       "::",
         
         NEWLINE     "\n"
     }
 }
 
+impl TokenKind {
+    // Determines whether a BasicToken is a keyword, operator or delimiter.
+    pub fn Classify(kind: &BasicTokenKind, literal: &str, lookup: &std::collections::HashMap<String, TokenKind>) -> TokenKind {
+        match kind {
+            BasicTokenKind::Ident => {
+                match lookup.get(literal) {
+                    None => { TokenKind::Ident }
+                    Some(v) => { v.to_owned() }
+                }
+            }
+            BasicTokenKind::Operator => {
+                match lookup.get(literal) {
+                    None => { TokenKind::Operator }
+                    Some(v) => { v.to_owned() }
+                }
+            }
+            BasicTokenKind::Delimiter => { lookup.get(literal).expect("your scanner has design error").to_owned() }
+            BasicTokenKind::Int(format) => { TokenKind::Int(*format) }
+            BasicTokenKind::Float(format) => { TokenKind::Float(*format) }
+            BasicTokenKind::String => { TokenKind::String }
+            BasicTokenKind::StringHead => { TokenKind::StringHead }
+            BasicTokenKind::StringMiddle => { TokenKind::StringMiddle }
+            BasicTokenKind::StringTail => { TokenKind::StringTail }
+            BasicTokenKind::Char => { TokenKind::Char }
+            BasicTokenKind::Comment | BasicTokenKind::DocComment => { TokenKind::Comment }
+            BasicTokenKind::Invalid => { TokenKind::Invalid }
+        }
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct Token {
     pub Pos: PosRange,
     pub Kind: TokenKind,
-    pub Literal: String,
+    // Interned by the parser, so repeated identifiers share one allocation.
+    pub Literal: Rc<str>,
 }
 
 impl Display for Token {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {}", self.Pos, &self.Literal)
+        write!(f, "{}", &self.Literal)
     }
 }
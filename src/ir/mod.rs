@@ -1 +1,2 @@
-mod types;
\ No newline at end of file
+pub mod types;
+mod types_test;
@@ -9,3 +9,133 @@ pub struct FuncType {}
 pub struct StructType {}
 
 pub struct TraitType {}
+
+// Size and alignment in bytes.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Layout {
+    pub Size: u64,
+    pub Align: u64,
+}
+
+impl Layout {
+    pub fn AlignTo(offset: u64, align: u64) -> u64 { (offset + align - 1) / align * align }
+}
+
+// Size of references and lengths, on 64-bit targets
+pub const Word: Layout = Layout { Size: 8, Align: 8 };
+
+// Len elements in place, each padded to its alignment.
+pub struct ArrayType {
+    pub Len: u64,
+    pub Elem: Layout,
+    pub Layout: Layout,
+}
+
+impl ArrayType {
+    pub fn new(len: u64, elem: Layout) -> ArrayType {
+        let stride = Layout::AlignTo(elem.Size, elem.Align.max(1));
+        ArrayType { Len: len, Elem: elem, Layout: Layout { Size: stride * len, Align: elem.Align.max(1) } }
+    }
+}
+
+// A reference to the first element and the element count, in two words.
+pub struct SliceType {
+    pub Elem: Layout,
+    pub Layout: Layout,
+}
+
+impl SliceType {
+    pub fn new(elem: Layout) -> SliceType {
+        SliceType { Elem: elem, Layout: Layout { Size: 2 * Word.Size, Align: Word.Align } }
+    }
+}
+
+// Elements in order, each at the first offset past the previous one aligned for it.
+pub struct TupleType {
+    pub Elements: Vec<Layout>,
+    pub Offsets: Vec<u64>,
+    pub Layout: Layout,
+}
+
+impl TupleType {
+    pub fn new(elements: Vec<Layout>) -> TupleType {
+        let mut offsets: Vec<u64> = vec![];
+        let mut size = 0;
+        let mut align = 1;
+        for e in &elements {
+            let offset = Layout::AlignTo(size, e.Align.max(1));
+            offsets.push(offset);
+            size = offset + e.Size;
+            align = align.max(e.Align);
+        }
+        TupleType { Elements: elements, Offsets: offsets, Layout: Layout { Size: Layout::AlignTo(size, align), Align: align } }
+    }
+}
+
+// A T or nothing, laid out as the union of `None` and `Some(T)` in that order.
+pub struct OptionalType {
+    pub Elem: Layout,
+    pub Union: UnionType,
+}
+
+impl OptionalType {
+    pub fn new(elem: Layout) -> OptionalType {
+        OptionalType { Elem: elem, Union: UnionType::new("Optional".to_string(), vec![("None".to_string(), Layout::default()), ("Some".to_string(), elem)]) }
+    }
+}
+
+// `&T` or `&mut T`, one word. Only the borrow checker sees the difference.
+pub struct RefType {
+    pub Mutable: bool,
+    pub Elem: Layout,
+    pub Layout: Layout,
+}
+
+impl RefType {
+    pub fn new(mutable: bool, elem: Layout) -> RefType {
+        RefType { Mutable: mutable, Elem: elem, Layout: Word }
+    }
+}
+
+pub struct UnionVariant {
+    pub Name: String,
+    // Tag value selecting the variant, its index in declaration order
+    pub Tag: u32,
+    pub Payload: Layout,
+}
+
+// Tagged union. The tag comes first, in the smallest unsigned integer holding every tag value;
+// all payloads share the storage at PayloadOffset, the first offset past the tag aligned for every payload.
+// The whole is aligned for the tag and every payload, its size rounded up to that alignment.
+pub struct UnionType {
+    pub Name: String,
+    pub Variants: Vec<UnionVariant>,
+    pub Tag: Layout,
+    pub PayloadOffset: u64,
+    pub Layout: Layout,
+}
+
+impl UnionType {
+    // Lays out the union from its variants' payloads, in declaration order.
+    pub fn new(name: String, variants: Vec<(String, Layout)>) -> UnionType {
+        let tagSize = match variants.len() {
+            0..=0x100 => { 1 }
+            0x101..=0x10000 => { 2 }
+            _ => { 4 }
+        };
+        let tag = Layout { Size: tagSize, Align: tagSize };
+
+        let payloadAlign = variants.iter().map(|(_, it)| it.Align).max().unwrap_or(1).max(1);
+        let payloadSize = variants.iter().map(|(_, it)| it.Size).max().unwrap_or(0);
+        let payloadOffset = Layout::AlignTo(tag.Size, payloadAlign);
+
+        let align = payloadAlign.max(tag.Align);
+        UnionType {
+            Name: name,
+            Variants: variants.into_iter().enumerate().map(|(i, (name, payload))| UnionVariant { Name: name, Tag: i as u32, Payload: payload }).collect(),
+            Tag: tag,
+            PayloadOffset: payloadOffset,
+            Layout: Layout { Size: Layout::AlignTo(payloadOffset + payloadSize, align), Align: align },
+        }
+    }
+}
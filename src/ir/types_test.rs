@@ -0,0 +1,43 @@
+// Copyright 2024 LangVM Project
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use crate::ir::types::*;
+
+#[test]
+fn TestUnionType_Layout() {
+    let shape = UnionType::new("Shape".to_string(), vec![
+        ("Circle".to_string(), Layout { Size: 8, Align: 8 }),
+        ("Rect".to_string(), Layout { Size: 16, Align: 8 }),
+        ("Empty".to_string(), Layout::default()),
+    ]);
+    assert_eq!(shape.Tag, Layout { Size: 1, Align: 1 });
+    assert_eq!(shape.PayloadOffset, 8);
+    assert_eq!(shape.Layout, Layout { Size: 24, Align: 8 });
+    assert_eq!(shape.Variants[2].Tag, 2);
+
+    let flags = UnionType::new("Flags".to_string(), (0..300).map(|i| (i.to_string(), Layout { Size: 1, Align: 1 })).collect());
+    assert_eq!(flags.Tag, Layout { Size: 2, Align: 2 });
+    assert_eq!(flags.PayloadOffset, 2);
+    assert_eq!(flags.Layout, Layout { Size: 4, Align: 2 });
+}
+
+#[test]
+fn TestCompositeType_Layout() {
+    let rgb = Layout { Size: 3, Align: 1 };
+    let u32 = Layout { Size: 4, Align: 4 };
+
+    assert_eq!(ArrayType::new(4, rgb).Layout, Layout { Size: 12, Align: 1 });
+    assert_eq!(ArrayType::new(0, u32).Layout, Layout { Size: 0, Align: 4 });
+    assert_eq!(SliceType::new(rgb).Layout, Layout { Size: 16, Align: 8 });
+    assert_eq!(RefType::new(true, rgb).Layout, Word);
+
+    let tuple = TupleType::new(vec![rgb, u32, rgb]);
+    assert_eq!(tuple.Offsets, vec![0, 4, 8]);
+    assert_eq!(tuple.Layout, Layout { Size: 12, Align: 4 });
+
+    let optional = OptionalType::new(u32);
+    assert_eq!(optional.Union.PayloadOffset, 4);
+    assert_eq!(optional.Union.Layout, Layout { Size: 8, Align: 4 });
+    assert_eq!(optional.Union.Variants[1].Name, "Some");
+}
@@ -6,16 +6,48 @@ use std::fmt;
 use std::fmt::Formatter;
 
 use crate::ast::Node;
+use crate::scanner::{BasicScannerError, PosRange, SourceMap};
 
 pub enum SyntaxError {
-    UnexpectedNode(UnexpectedNodeError)
+    UnexpectedNode(UnexpectedNodeError),
+    // Recovered by the scanner, which left an Invalid token in place
+    Lexical(BasicScannerError),
+    MissingInitializer(MissingInitializerError),
+}
+
+impl SyntaxError {
+    pub fn Render(&self, sm: &SourceMap) -> String {
+        match self {
+            SyntaxError::UnexpectedNode(e) => { e.Render(sm) }
+            SyntaxError::Lexical(e) => { e.Render(sm) }
+            SyntaxError::MissingInitializer(e) => { e.Render(sm) }
+        }
+    }
 }
 
 pub struct UnexpectedNodeError {
+    pub Pos: PosRange,
     pub Want: Node,
     pub Have: Node,
 }
 
+impl UnexpectedNodeError {
+    pub fn Render(&self, sm: &SourceMap) -> String { format!("{}: unexpected node: want {} but have {}", sm.Render(self.Pos), self.Want, self.Have) }
+}
+
 impl fmt::Debug for UnexpectedNodeError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "unexpected node: {} but have {}", self.Have, self.Want) }
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{}: unexpected node: want {} but have {}", self.Pos, self.Want, self.Have) }
+}
+
+// `let` or `mut` without a value, where neither a type nor a single binding allows initializing it later.
+pub struct MissingInitializerError {
+    pub Pos: PosRange,
+}
+
+impl MissingInitializerError {
+    pub fn Render(&self, sm: &SourceMap) -> String { format!("{}: missing initializer", sm.Render(self.Pos)) }
+}
+
+impl fmt::Debug for MissingInitializerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{}: missing initializer", self.Pos) }
 }
@@ -2,9 +2,8 @@
 // This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
-use std::collections::HashMap;
-
-use err_rs::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::rc::Rc;
 
 use crate::ast;
 use crate::parser::*;
@@ -12,7 +11,7 @@ use crate::scanner::*;
 use crate::tag_matches;
 
 pub trait AstNodeParserTrait<T> {
-    fn Expect(p: &mut Parser) -> Result<T, ParserError>;
+    fn Expect(p: &mut Parser<'_>) -> Result<T, ParserError>;
 }
 
 #[derive(Debug)]
@@ -20,16 +19,26 @@ pub enum ParserError {
     ScannerError(BasicScannerError),
 }
 
-pub struct Parser {
-    pub Scanner: BasicScanner,
+pub struct Parser<'a> {
+    pub Scanner: BasicScanner<'a>,
 
     pub KeywordLookup: HashMap<String, ast::TokenKind>,
 
+    // Current token, not consumed yet
     pub Token: ast::Token,
 
+    // Tokens after the current one that were peeked, each with its doc comments
+    pub Lookahead: VecDeque<(ast::Token, Vec<ast::Token>)>,
+
+    // End of the last consumed token
+    pub LastEnd: Position,
+
     // Insert semicolon when true
     pub CompleteSemicolon: bool,
 
+    // In an if, for or match header, where `{` after a name starts the body rather than a struct literal
+    pub NoStructLiteral: bool,
+
     // Recover from error
     pub QuoteStack: Vec<ast::TokenKind>,
 
@@ -38,116 +47,291 @@ pub struct Parser {
 
     // Package names
     pub NamespaceIdents: HashMap<String, ast::ImportDecl>,
+
+    // Doc comments preceding the current token
+    pub Docs: Vec<ast::Token>,
+
+    // Interned token literals
+    pub Literals: HashSet<Rc<str>>,
 }
 
-impl Parser {
-    pub fn new(buffer: Vec<char>) -> Parser {
+impl<'a> Parser<'a> {
+    pub fn new(buffer: &'a str) -> Parser<'a> {
         Parser {
-            Scanner: BasicScanner {
-                BufferScanner: BufferScanner::new(buffer),
-                Delimiters: vec!['(', ')', '[', ']', '{', '}', ',', ';', '/', '\n'],
-                Whitespaces: vec![' ', '\t', '\r'],
-            },
+            Scanner: BasicScanner::new(
+                buffer,
+                vec!['(', ')', '[', ']', '{', '}', ',', ';', '\n'],
+                vec![' ', '\t', '\r'],
+                ast::TokenKind::Operators(),
+            ),
             KeywordLookup: ast::TokenKind::KeywordLookup(),
             Token: ast::Token::default(),
 
+            Lookahead: VecDeque::new(),
+
+            LastEnd: Position::default(),
+
             CompleteSemicolon: false,
 
+            NoStructLiteral: false,
+
             QuoteStack: vec![],
 
             SyntaxErrors: vec![],
 
             NamespaceIdents: HashMap::default(),
+
+            Docs: vec![],
+
+            Literals: HashSet::default(),
         }
     }
 
-    pub fn GetPos(&self) -> Position { self.Scanner.GetPos() }
+    pub fn FromFile(file: &'a SourceFile) -> Parser<'a> {
+        let mut p = Parser::new(&file.Buffer);
+        p.Scanner.BufferScanner.File = file.Id;
+        p
+    }
 
-    pub fn Scan(&mut self) -> Result<&ast::Token, ParserError> {
-        let bt = wrap_result!(ParserError::ScannerError, self.Scanner.Scan());
+    // Parses a whole file, returning the tree with every syntax error and scanner warning found on the way.
+    pub fn ParseFile(file: &'a SourceFile) -> Result<(ast::File, Vec<SyntaxError>, Vec<BasicScannerWarning>), ParserError> {
+        let mut p = Parser::FromFile(file);
+        p.Bump();
 
-        // Semicolon complete: replace newline to semicolon
-        match self.Token.Kind {
-            ast::TokenKind::NEWLINE => {
-                if self.CompleteSemicolon {
-                    self.CompleteSemicolon = false;
-                    self.Token = ast::Token {
-                        Pos: bt.Pos,
-                        Kind: ast::TokenKind::SEMICOLON,
-                        Literal: String::from(";"),
-                    };
-                    ok!(&self.Token);
-                }
+        let tree = ast::File::Expect(&mut p)?;
+        Ok((tree, std::mem::take(&mut p.SyntaxErrors), std::mem::take(&mut p.Scanner.Warnings)))
+    }
+
+    // Beginning of the current token
+    pub fn GetPos(&self) -> Position { self.Token.Pos.Begin }
+
+    pub fn Intern(&mut self, literal: &str) -> Rc<str> {
+        match self.Literals.get(literal) {
+            Some(it) => { it.clone() }
+            None => {
+                let it: Rc<str> = Rc::from(literal);
+                self.Literals.insert(it.clone());
+                it
             }
-            _ => {}
         }
+    }
 
-        self.CompleteSemicolon = false;
+    // Whether a newline after the token ends a statement.
+    fn CompletesSemicolon(kind: &ast::TokenKind) -> bool {
+        match kind {
+            ast::TokenKind::Ident | ast::TokenKind::Int(_) | ast::TokenKind::Float(_) | ast::TokenKind::Char
+            | ast::TokenKind::String | ast::TokenKind::StringTail | ast::TokenKind::Invalid
+            | ast::TokenKind::RPAREN | ast::TokenKind::RBRACK | ast::TokenKind::RBRACE
+            | ast::TokenKind::BREAK | ast::TokenKind::CONTINUE | ast::TokenKind::RETURN | ast::TokenKind::QUESTION => { true }
+            _ => { false }
+        }
+    }
 
-        let literal = bt.Literal.iter().collect::<String>();
+    // Scans the token after the last peeked one. Comments are skipped and doc comments go with the token they precede.
+    // Semicolon complete: a newline becomes a semicolon after a token that can end a statement, and is skipped otherwise.
+    fn Next(&mut self) -> (ast::Token, Vec<ast::Token>) {
+        let mut docs: Vec<ast::Token> = vec![];
 
-        // Determines whether BasicToken is a keyword, operator or delimiter.
-        let kind = match bt.Kind {
-            BasicTokenKind::Ident => {
-                match self.KeywordLookup.get(&literal) {
-                    None => { ast::TokenKind::Ident }
-                    Some(v) => { v.to_owned() }
+        loop {
+            let bt = match self.Scanner.next() {
+                Some(bt) => { bt }
+                None => {
+                    let pos = PosRange { File: self.Scanner.BufferScanner.File, Begin: self.Scanner.GetPos(), End: self.Scanner.GetPos() };
+                    let kind = if self.CompleteSemicolon { ast::TokenKind::SEMICOLON } else { ast::TokenKind::EOF };
+                    self.CompleteSemicolon = false;
+                    return (ast::Token { Pos: pos, Kind: kind, Literal: Rc::default() }, docs);
                 }
+            };
+            for e in self.Scanner.Errors.drain(..) {
+                self.SyntaxErrors.push(SyntaxError::Lexical(e));
             }
-            BasicTokenKind::Operator => {
-                match self.KeywordLookup.get(&literal) {
-                    None => { ast::TokenKind::Operator }
-                    Some(v) => { v.to_owned() }
+
+            match bt.Kind {
+                BasicTokenKind::Comment => { continue; }
+                BasicTokenKind::DocComment => {
+                    docs.push(ast::Token {
+                        Pos: bt.Pos,
+                        Kind: ast::TokenKind::Comment,
+                        Literal: Rc::from(bt.Literal.as_ref()),
+                    });
+                    continue;
                 }
+                _ => {}
             }
-            BasicTokenKind::Delimiter => {
-                let kind = self.KeywordLookup.get(&literal).expect("your scanner has design error");
-                match kind {
-                    ast::TokenKind::LPAREN => { self.QuoteStack.push(ast::TokenKind::RPAREN) }
-                    ast::TokenKind::LBRACE => { self.QuoteStack.push(ast::TokenKind::RBRACE) }
-                    ast::TokenKind::LBRACK => { self.QuoteStack.push(ast::TokenKind::RBRACK) }
-                    _ => {}
+
+            let mut kind = ast::TokenKind::Classify(&bt.Kind, &bt.Literal, &self.KeywordLookup);
+            if tag_matches!(&kind, &ast::TokenKind::NEWLINE) {
+                // an interpolated expression runs to its `}` whatever the lines it spans
+                if !self.CompleteSemicolon || self.Scanner.Interpolations.last() == Some(&0) {
+                    continue;
                 }
-                kind.to_owned()
+                kind = ast::TokenKind::SEMICOLON;
             }
-            BasicTokenKind::Int(format) => { ast::TokenKind::Int(format) }
-            BasicTokenKind::Float => { ast::TokenKind::Float } // TODO
-            BasicTokenKind::String => { ast::TokenKind::String }
-            BasicTokenKind::Char => { ast::TokenKind::Char }
-            BasicTokenKind::Comment => { return self.Scan(); }
+            self.CompleteSemicolon = Parser::CompletesSemicolon(&kind);
+
+            let tok = ast::Token {
+                Pos: bt.Pos,
+                Kind: kind,
+                Literal: self.Intern(&bt.Literal),
+            };
+            return (tok, docs);
+        }
+    }
+
+    fn AtEOF(tok: &ast::Token) -> bool { tag_matches!(&tok.Kind, &ast::TokenKind::EOF) }
+
+    // Fills the lookahead buffer up to n tokens, or up to the end of file.
+    fn Fill(&mut self, n: usize) {
+        while self.Lookahead.len() < n && !self.Lookahead.back().map_or(false, |(tok, _)| Parser::AtEOF(tok)) {
+            let next = self.Next();
+            self.Lookahead.push_back(next);
+        }
+    }
+
+    // The n-th token after the current one, Peek(0) being the current token. Past the end of file it is EOF.
+    pub fn Peek(&mut self, n: usize) -> &ast::Token {
+        if n == 0 {
+            return &self.Token;
+        }
+        self.Fill(n);
+        let i = (n - 1).min(self.Lookahead.len() - 1);
+        &self.Lookahead[i].0
+    }
+
+    // Consumes the current token and returns it. At the end of file the current token stays EOF.
+    pub fn Bump(&mut self) -> ast::Token {
+        self.Fill(1);
+        let (tok, docs) = match self.Lookahead.front() {
+            Some((tok, _)) if Parser::AtEOF(tok) => { (tok.clone(), vec![]) }
+            _ => { self.Lookahead.pop_front().unwrap() }
         };
 
-        self.Token = ast::Token {
-            Pos: bt.Pos,
-            Kind: kind,
-            Literal: bt.Literal.iter().collect(),
+        match tok.Kind {
+            ast::TokenKind::LPAREN => { self.QuoteStack.push(ast::TokenKind::RPAREN) }
+            ast::TokenKind::LBRACE => { self.QuoteStack.push(ast::TokenKind::RBRACE) }
+            ast::TokenKind::LBRACK => { self.QuoteStack.push(ast::TokenKind::RBRACK) }
+            ast::TokenKind::RPAREN | ast::TokenKind::RBRACE | ast::TokenKind::RBRACK => {
+                if self.QuoteStack.last().map_or(false, |it| tag_matches!(it, &tok.Kind)) {
+                    self.QuoteStack.pop();
+                }
+            }
+            _ => {}
+        }
+
+        self.Docs = docs;
+        self.LastEnd = self.Token.Pos.End;
+        std::mem::replace(&mut self.Token, tok)
+    }
+
+    // Whether the current token is of the kind, e.g. `p.At(TokenKind::LPAREN)`.
+    pub fn At(&self, kind: ast::TokenKind) -> bool { tag_matches!(&self.Token.Kind, &kind) }
+
+    // Consumes the current token if it is of the kind.
+    pub fn Eat(&mut self, kind: ast::TokenKind) -> bool {
+        if self.At(kind) {
+            self.Bump();
+            return true;
+        }
+        false
+    }
+
+    // Consumes a `>`. The scanner splits `>>`, `>=` and `>>=` as operators, so their first `>` is consumed
+    // and the rest left as the current token, closing nested type arguments one at a time.
+    pub fn EatGreater(&mut self) -> bool {
+        let rest = match self.Token.Kind {
+            ast::TokenKind::GTR => { return self.Eat(ast::TokenKind::GTR); }
+            ast::TokenKind::SHR => { ast::TokenKind::GTR }
+            ast::TokenKind::GEQ => { ast::TokenKind::ASSIGN }
+            ast::TokenKind::SHR_ASSIGN => { ast::TokenKind::GEQ }
+            _ => { return false; }
         };
 
+        let begin = self.Token.Pos.Begin;
+        let split = Position { Offset: begin.Offset + 1, Line: begin.Line, Column: begin.Column + 1 };
+        self.LastEnd = split;
+        self.Token.Pos.Begin = split;
+        self.Token.Literal = self.Intern(&self.Token.Literal[1..].to_string());
+        self.Token.Kind = rest;
+        true
+    }
+
+    // Consumes the `>` closing type arguments or parameters. A newline after it ends a statement as one after `)`
+    // does, e.g. after the type of `Items List<T>`; `>` itself completes no semicolon, since a comparison may go on
+    // to the next line.
+    pub fn EatClosingGreater(&mut self) -> bool {
+        if !self.At(ast::TokenKind::GTR) {
+            return self.EatGreater();
+        }
+
+        let end = self.Token.Pos.End;
+        let next = self.Peek(1);
+        if next.Pos.Begin.Line > end.Line && !tag_matches!(&next.Kind, &ast::TokenKind::SEMICOLON) {
+            self.LastEnd = end;
+            self.Docs = vec![];
+            self.Token = ast::Token { Pos: PosRange { File: self.Token.Pos.File, Begin: end, End: end }, Kind: ast::TokenKind::SEMICOLON, Literal: Rc::default() };
+            return true;
+        }
+        self.Bump();
+        true
+    }
+
+    // Runs f with struct literals allowed or not, restoring the previous setting after.
+    pub fn WithStructLiteral<T>(&mut self, allow: bool, f: impl FnOnce(&mut Parser<'a>) -> Result<T, ParserError>) -> Result<T, ParserError> {
+        let saved = std::mem::replace(&mut self.NoStructLiteral, !allow);
+        let result = f(self);
+        self.NoStructLiteral = saved;
+        result
+    }
+
+    // Moves to the next token like Bump, but fails at the end of file.
+    pub fn Scan(&mut self) -> Result<&ast::Token, ParserError> {
+        self.Bump();
+        if self.At(ast::TokenKind::EOF) {
+            return Err(ParserError::ScannerError(BasicScannerError::EOF(EOFError { Pos: self.Token.Pos })));
+        }
         Ok(&self.Token)
     }
 
+    // Takes the doc comments preceding the current token, to attach them to the node it begins.
+    pub fn TakeDoc(&mut self) -> ast::Optional<ast::Doc> {
+        if self.Docs.is_empty() {
+            return ast::Optional::None;
+        }
+
+        let comments = std::mem::take(&mut self.Docs);
+
+        ast::Optional::Some(ast::Doc {
+            Pos: PosRange { File: comments[0].Pos.File, Begin: comments[0].Pos.Begin, End: comments[comments.len() - 1].Pos.End },
+            Text: comments.iter().map(|it| it.Literal.as_ref()).collect::<Vec<&str>>().join("\n"),
+            Comments: comments,
+        })
+    }
+
     pub fn Report(&mut self, e: SyntaxError) {
         self.SyntaxErrors.push(e);
     }
 
+    // Reports the error and skips to the closing delimiter of the innermost open group.
     pub fn ReportAndRecover(&mut self, e: SyntaxError) -> Result<(), ParserError> {
         self.SyntaxErrors.push(e);
 
-        if self.QuoteStack.len() != 0 {
-            while !tag_matches!(&self.Token.Kind, &self.QuoteStack.pop().unwrap()) {
-                self.Scan()?;
+        let depth = self.QuoteStack.len();
+        if depth != 0 {
+            while self.QuoteStack.len() >= depth && !self.At(ast::TokenKind::EOF) {
+                self.Bump();
             }
         }
 
         Ok(())
     }
 
-    pub fn MatchTerm(&mut self, term: ast::TokenKind) -> Result<&ast::Token, ParserError> {
-        let tok = self.Scan()?;
-        if tag_matches!(&tok.Kind, &term) {
-            self.Report(SyntaxError::UnexpectedNode(UnexpectedNodeError { Want: ast::Node::TokenKind(term), Have: ast::Node::TokenKind(tok.Kind.clone()) }));
+    // Consumes the current token if it is `term`; otherwise reports it and leaves it in place.
+    pub fn MatchTerm(&mut self, term: ast::TokenKind) -> Result<ast::Token, ParserError> {
+        if !self.At(term.clone()) {
+            self.Report(SyntaxError::UnexpectedNode(UnexpectedNodeError { Pos: self.Token.Pos, Want: ast::Node::TokenKind(term), Have: ast::Node::Token(self.Token.clone()) }));
+            return Ok(self.Token.clone());
         }
-        Ok(tok)
+        Ok(self.Bump())
     }
 }
 
@@ -160,7 +344,7 @@ macro_rules! def_parser {
     ) => {
         $(
         impl crate::parser::AstNodeParserTrait<$ast_node> for $ast_node {
-            fn Expect($p: &mut crate::parser::Parser) -> Result<$ast_node, ParserError> { Ok($block) }
+            fn Expect($p: &mut crate::parser::Parser<'_>) -> Result<$ast_node, ParserError> { Ok($block) }
         }
         )*
     };
@@ -7,9 +7,9 @@ use crate::parser::parser::{Parser, ParserError};
 #[test]
 fn TestParser_SemicolonComplete() {
     fn test() -> Result<(), ParserError> {
-        let mut p = Parser::new(String::from(r#"
+        let mut p = Parser::new(r#"
         {}
-        "#).chars().collect());
+        "#);
 
         loop {
             p.Scan()?;
@@ -24,3 +24,136 @@ fn TestParser_SemicolonComplete() {
         }
     }
 }
+
+#[test]
+fn TestParser_TakeDoc() {
+    let mut p = Parser::new("/// Adds\n/** numbers */\nfunc x\n/// dangling\ny func");
+
+    while &*p.Token.Literal != "func" {
+        p.Scan().unwrap();
+    }
+    match p.TakeDoc() {
+        crate::ast::Optional::Some(doc) => {
+            assert_eq!(doc.Text, " Adds\n numbers ");
+            assert_eq!(doc.Comments.len(), 2);
+        }
+        crate::ast::Optional::None => { panic!() }
+    }
+
+    p.Scan().unwrap();
+    while &*p.Token.Literal != "func" {
+        p.Scan().unwrap();
+    }
+    assert!(matches!(p.TakeDoc(), crate::ast::Optional::None));
+}
+
+#[test]
+fn TestParser_FromFile() {
+    use crate::scanner::SourceMap;
+
+    let mut sm = SourceMap::default();
+    let id = sm.Add(String::from("main.cee"), String::from("\n  \"unterminated"));
+
+    let mut p = Parser::FromFile(sm.Get(id));
+    while p.Scan().is_ok() {}
+    assert_eq!(p.SyntaxErrors.len(), 1);
+    assert_eq!(p.SyntaxErrors[0].Render(&sm), "main.cee:2:3: unterminated string");
+}
+
+#[test]
+fn TestParser_Operator() {
+    use crate::ast::TokenKind;
+
+    let mut p = Parser::new("a::b => c..d ?; e>>=f >= g");
+    let mut kinds = vec![];
+    while p.Scan().is_ok() {
+        kinds.push(p.Token.Kind.clone());
+    }
+    let want = vec![
+        TokenKind::Ident, TokenKind::SCOPE, TokenKind::Ident, TokenKind::ARROW, TokenKind::Ident, TokenKind::RANGE, TokenKind::Ident, TokenKind::QUESTION, TokenKind::SEMICOLON,
+        TokenKind::Ident, TokenKind::SHR_ASSIGN, TokenKind::Ident, TokenKind::GEQ, TokenKind::Ident, TokenKind::SEMICOLON,
+    ];
+    assert_eq!(kinds.len(), want.len());
+    for (have, want) in kinds.iter().zip(want.iter()) {
+        assert!(crate::tag_matches!(have, want), "want {:?} but have {:?}", want, have);
+    }
+}
+
+#[test]
+fn TestParser_Lookahead() {
+    use crate::ast::TokenKind;
+
+    let mut p = Parser::new("f(a)\n\nx = [1,\n2]\n");
+    p.Bump();
+
+    assert!(p.At(TokenKind::Ident));
+    assert!(matches!(p.Peek(1).Kind, TokenKind::LPAREN));
+    assert!(matches!(p.Peek(4).Kind, TokenKind::SEMICOLON)); // newline after `)`
+    assert!(matches!(p.Peek(5).Kind, TokenKind::Ident));
+    assert!(matches!(p.Peek(100).Kind, TokenKind::EOF));
+
+    assert_eq!(&*p.Bump().Literal, "f");
+    assert!(!p.Eat(TokenKind::RPAREN));
+    assert!(p.Eat(TokenKind::LPAREN));
+    assert_eq!(p.QuoteStack.len(), 1);
+    let kinds = std::iter::from_fn(|| if p.At(TokenKind::EOF) { None } else { Some(p.Bump().Kind) }).collect::<Vec<_>>();
+    assert_eq!(kinds.len(), 11); // a ) ; x = [ 1 , 2 ] ;
+    assert!(p.QuoteStack.is_empty());
+    assert!(matches!(p.Bump().Kind, TokenKind::EOF));
+}
+
+#[test]
+fn TestParser_ParseFile() {
+    use crate::ast::*;
+    use crate::scanner::{BasicScannerWarning, SourceMap};
+
+    let mut sm = SourceMap::default();
+    let id = sm.Add(String::from("main.cee"), String::from(r#"package main
+
+import fmt "std/fmt"
+import io "std/io"
+
+/// Maximum count
+const Max int = 1 << 8
+struct Point {
+    X, Y int
+}
+union Shape {
+    Empty
+}
+trait Reader
+func Read(r Reader) <- int
+) x + 1; 2
+] y
+func After()
+"#));
+
+    let (file, errors, warnings) = Parser::ParseFile(sm.Get(id)).unwrap();
+    assert!(matches!(&file.Package, Optional::Some(it) if it.Name.Token.Literal.as_ref() == "main"));
+    assert_eq!(file.Imports.Elements.len(), 2);
+    assert_eq!(file.Imports.Elements[1].Canonical.Literal.as_ref(), "std/io");
+
+    let decls = &file.Decls.Elements;
+    assert_eq!(decls.len(), 6);
+    match &decls[0] {
+        Decl::ConstDecl(it) => {
+            assert_eq!(it.Value.to_string(), "(1 << 8)");
+            assert!(matches!(&it.Doc, Optional::Some(doc) if doc.Text == " Maximum count"));
+        }
+        _ => { panic!() }
+    }
+    assert!(matches!(decls[1], Decl::StructType(_)));
+    assert!(matches!(decls[2], Decl::UnionType(_)));
+    assert!(matches!(decls[3], Decl::TraitType(_)));
+    assert!(matches!(decls[4], Decl::FuncDecl(_)));
+    assert!(matches!(&decls[5], Decl::FuncDecl(it) if it.Name.to_string() == "After"));
+
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].Render(&sm).starts_with("main.cee:16:1: unexpected node"));
+    assert!(warnings.is_empty());
+
+    let id = sm.Add(String::from("warn.cee"), String::from("func p\u{430}ypal()\n"));
+    let (_, errors, warnings) = Parser::ParseFile(sm.Get(id)).unwrap();
+    assert!(errors.is_empty());
+    assert!(matches!(&warnings[..], [BasicScannerWarning::MixedScriptIdent(_)]));
+}
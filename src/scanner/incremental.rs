@@ -0,0 +1,159 @@
+// Copyright 2024 LangVM Project
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::scanner::*;
+
+// Replaces the source between two byte offsets, which must lie on char boundaries.
+pub struct TextEdit {
+    pub Begin: usize,
+    pub End: usize,
+    pub Text: String,
+}
+
+pub struct ScannedToken {
+    pub Token: BasicToken<'static>,
+    // Interpolation depths before the token, the only scanner state needed to restart there
+    pub Interpolations: Vec<usize>,
+    // Lexical errors met while scanning the token
+    pub Errors: Vec<BasicScannerError>,
+}
+
+// Keeps the tokens of an edited buffer up to date. An edit re-scans from the token before it
+// until the scanner reaches the start of an old token in the same state; the tokens from there on
+// are reused with their positions shifted.
+pub struct IncrementalScanner {
+    pub File: FileId,
+    pub Buffer: String,
+    pub Tokens: Vec<ScannedToken>,
+
+    pub Delimiters: Vec<char>,
+    pub Whitespaces: Vec<char>,
+    pub Operators: Vec<&'static str>,
+}
+
+// Position after scanning `text` from `pos`.
+fn Advance(mut pos: Position, text: &str) -> Position {
+    for ch in text.chars() {
+        if ch == '\n' {
+            pos.Line += 1;
+            pos.Column = 0;
+        } else {
+            pos.Column += 1;
+        }
+        pos.Offset += ch.len_utf8();
+    }
+    pos
+}
+
+impl IncrementalScanner {
+    pub fn new(buffer: String, delimiters: Vec<char>, whitespaces: Vec<char>, operators: Vec<&'static str>) -> IncrementalScanner {
+        let mut s = IncrementalScanner {
+            File: FileId::default(),
+            Buffer: buffer,
+            Tokens: vec![],
+            Delimiters: delimiters,
+            Whitespaces: whitespaces,
+            Operators: operators,
+        };
+        s.Tokens = s.ScanFrom(Position::default(), vec![], |_, _| { None }).0;
+        s
+    }
+
+    // Scans from `begin` to the end of buffer, or until `resume` names the old token to continue with.
+    fn ScanFrom(&self, begin: Position, interpolations: Vec<usize>, mut resume: impl FnMut(&BasicToken, &[usize]) -> Option<usize>) -> (Vec<ScannedToken>, Option<usize>) {
+        let mut scanner = BasicScanner::new(&self.Buffer, self.Delimiters.clone(), self.Whitespaces.clone(), self.Operators.clone());
+        scanner.BufferScanner.File = self.File;
+        scanner.BufferScanner.Pos = begin;
+        scanner.Interpolations = interpolations;
+
+        let mut tokens: Vec<ScannedToken> = vec![];
+        loop {
+            let interpolations = scanner.Interpolations.clone();
+            let tok = match scanner.Scan() {
+                Ok(tok) => { tok }
+                Err(_) => { return (tokens, None); }
+            };
+            if let Some(i) = resume(&tok, &interpolations) {
+                return (tokens, Some(i));
+            }
+            tokens.push(ScannedToken {
+                Token: BasicToken { Pos: tok.Pos, Kind: tok.Kind, Literal: Cow::Owned(tok.Literal.into_owned()) },
+                Interpolations: interpolations,
+                Errors: scanner.Errors.drain(..).collect(),
+            });
+        }
+    }
+
+    // Applies the edit and returns the index range of the re-scanned tokens.
+    pub fn Apply(&mut self, edit: &TextEdit) -> Range<usize> {
+        // Scanning a token may look up to two chars past its end, so restart at the last token
+        // ending before the edit: no token ahead of it can see the edited text.
+        let first = self.Tokens.partition_point(|it| it.Token.Pos.End.Offset < edit.Begin);
+        let (keep, begin, interpolations) = match first {
+            0 => { (0, Position::default(), vec![]) }
+            _ => {
+                let it = &self.Tokens[first - 1];
+                (first - 1, it.Token.Pos.Begin, it.Interpolations.clone())
+            }
+        };
+
+        let editBegin = Advance(begin, &self.Buffer[begin.Offset..edit.Begin]);
+        let oldEnd = Advance(editBegin, &self.Buffer[edit.Begin..edit.End]);
+        let newEnd = Advance(editBegin, &edit.Text);
+
+        let shift = |pos: Position| -> Position {
+            Position {
+                Offset: pos.Offset - oldEnd.Offset + newEnd.Offset,
+                Line: pos.Line - oldEnd.Line + newEnd.Line,
+                Column: if pos.Line == oldEnd.Line { pos.Column - oldEnd.Column + newEnd.Column } else { pos.Column },
+            }
+        };
+
+        self.Buffer.replace_range(edit.Begin..edit.End, &edit.Text);
+
+        // Old tokens after the edit see the same text as before, so a new token starting where one of them
+        // starts, in the same state, is followed by the same tokens.
+        let mut next = self.Tokens.partition_point(|it| it.Token.Pos.Begin.Offset < oldEnd.Offset);
+        let old = &self.Tokens;
+        let (tokens, resumed) = self.ScanFrom(begin, interpolations, |tok, interpolations| {
+            while next < old.len() && shift(old[next].Token.Pos.Begin).Offset < tok.Pos.Begin.Offset {
+                next += 1;
+            }
+            match old.get(next) {
+                Some(it) if shift(it.Token.Pos.Begin).Offset == tok.Pos.Begin.Offset && it.Interpolations == interpolations => { Some(next) }
+                _ => { None }
+            }
+        });
+
+        let reused: Vec<ScannedToken> = match resumed {
+            Some(i) => {
+                self.Tokens.drain(i..).map(|mut it| {
+                    it.Token.Pos.Begin = shift(it.Token.Pos.Begin);
+                    it.Token.Pos.End = shift(it.Token.Pos.End);
+                    for err in &mut it.Errors {
+                        let pos = err.PosRangeMut();
+                        pos.Begin = shift(pos.Begin);
+                        pos.End = shift(pos.End);
+                    }
+                    it
+                }).collect()
+            }
+            None => { vec![] }
+        };
+
+        let changed = keep..keep + tokens.len();
+        self.Tokens.truncate(keep);
+        self.Tokens.extend(tokens);
+        self.Tokens.extend(reused);
+        changed
+    }
+
+    // Lexical errors of the whole buffer, in source order.
+    pub fn Errors(&self) -> impl Iterator<Item=&BasicScannerError> {
+        self.Tokens.iter().flat_map(|it| it.Errors.iter())
+    }
+}
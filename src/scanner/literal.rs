@@ -0,0 +1,87 @@
+// Copyright 2024 LangVM Project
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use crate::scanner::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IntSuffix {
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+}
+
+impl IntSuffix {
+    pub fn Lookup(suffix: &str) -> Option<IntSuffix> {
+        Some(match suffix {
+            "i8" => { IntSuffix::I8 }
+            "i16" => { IntSuffix::I16 }
+            "i32" => { IntSuffix::I32 }
+            "i64" => { IntSuffix::I64 }
+            "i128" => { IntSuffix::I128 }
+            "u8" => { IntSuffix::U8 }
+            "u16" => { IntSuffix::U16 }
+            "u32" => { IntSuffix::U32 }
+            "u64" => { IntSuffix::U64 }
+            "u128" => { IntSuffix::U128 }
+            _ => { return None; }
+        })
+    }
+
+    pub fn Bits(self) -> u32 {
+        match self {
+            IntSuffix::I8 | IntSuffix::U8 => { 8 }
+            IntSuffix::I16 | IntSuffix::U16 => { 16 }
+            IntSuffix::I32 | IntSuffix::U32 => { 32 }
+            IntSuffix::I64 | IntSuffix::U64 => { 64 }
+            IntSuffix::I128 | IntSuffix::U128 => { 128 }
+        }
+    }
+
+    pub fn Signed(self) -> bool {
+        match self {
+            IntSuffix::I8 | IntSuffix::I16 | IntSuffix::I32 | IntSuffix::I64 | IntSuffix::I128 => { true }
+            _ => { false }
+        }
+    }
+
+    // Largest literal accepted with this suffix. Literals carry no sign, so signed types accept
+    // the magnitude of their minimum, letting `-128i8` through; `128i8` unnegated is left to the checker.
+    pub fn Max(self) -> u128 {
+        match self.Signed() {
+            true => { 1 << (self.Bits() - 1) }
+            false => { u128::MAX >> (128 - self.Bits()) }
+        }
+    }
+}
+
+pub struct IntLiteral {
+    pub Value: u128,
+    pub Suffix: Option<IntSuffix>,
+}
+
+// Decodes the literal of an Int token: digits in the given format with optional '_' separators,
+// followed by an optional width suffix. Returns None when the value does not fit.
+pub fn DecodeInt(format: IntFormat, literal: &str) -> Option<IntLiteral> {
+    let (digits, suffix) = match literal.find(['i', 'u']) {
+        Some(i) => { (&literal[..i], Some(IntSuffix::Lookup(&literal[i..])?)) }
+        None => { (literal, None) }
+    };
+
+    let mut value: u128 = 0;
+    for ch in digits.chars().filter(|ch| *ch != '_') {
+        value = value.checked_mul(format as u128)?.checked_add(ch.to_digit(format as u32)? as u128)?;
+    }
+
+    match suffix {
+        Some(suffix) if value > suffix.Max() => { None }
+        _ => { Some(IntLiteral { Value: value, Suffix: suffix }) }
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright 2024 LangVM Project
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use crate::scanner::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TriviaKind {
+    Whitespace,
+    Newline,
+    Comment,
+    DocComment,
+    // Source the scanner could not make a token of
+    Skipped,
+}
+
+pub struct Trivia<'a> {
+    pub Pos: PosRange,
+    pub Kind: TriviaKind,
+    pub Text: &'a str,
+}
+
+// A token with the trivia around it. Trailing trivia runs to the end of the token's line,
+// everything after that is leading trivia of the next token.
+pub struct LosslessToken<'a> {
+    pub Leading: Vec<Trivia<'a>>,
+    pub Token: BasicToken<'a>,
+    pub Text: &'a str,
+    pub Trailing: Vec<Trivia<'a>>,
+}
+
+// End of the lossless token stream, holding the trivia after the last token.
+pub struct LosslessEOF<'a> {
+    pub Pos: Position,
+    pub Leading: Vec<Trivia<'a>>,
+}
+
+impl<'a> BasicScanner<'a> {
+    fn PushTrivia(&self, trivia: &mut Vec<Trivia<'a>>, begin: Position, kind: TriviaKind) {
+        let pos = PosRange { File: self.BufferScanner.File, Begin: begin, End: self.GetPos() };
+        trivia.push(Trivia { Pos: pos, Kind: kind, Text: self.Slice(pos) });
+    }
+
+    // Scans whitespace, comments, and newlines that are not delimiters. Trailing trivia stops before a newline.
+    pub fn ScanTrivia(&mut self, trailing: bool) -> Vec<Trivia<'a>> {
+        let mut trivia: Vec<Trivia<'a>> = vec![];
+
+        loop {
+            let begin = self.GetPos();
+            match self.PeekChar(0) {
+                Some(ch) if self.Whitespaces.contains(&ch) => {
+                    while self.PeekChar(0).map_or(false, |ch| self.Whitespaces.contains(&ch)) {
+                        let _ = self.Move();
+                    }
+                    self.PushTrivia(&mut trivia, begin, TriviaKind::Whitespace);
+                }
+                Some('\n') if !trailing && !self.Delimiters.contains(&'\n') => {
+                    let _ = self.Move();
+                    self.PushTrivia(&mut trivia, begin, TriviaKind::Newline);
+                }
+                Some('/') if self.PeekChar(1) == Some('/') || self.PeekChar(1) == Some('*') => {
+                    let kind = match self.ScanComment() {
+                        Ok(BasicToken { Kind: BasicTokenKind::DocComment, .. }) => { TriviaKind::DocComment }
+                        Ok(_) => { TriviaKind::Comment }
+                        Err(_) => { TriviaKind::Skipped } // unterminated, runs to the end of file
+                    };
+                    self.PushTrivia(&mut trivia, begin, kind);
+                }
+                _ => { break; }
+            }
+        }
+
+        trivia
+    }
+
+    // Scans the next token with its trivia. Malformed source comes back as an Invalid token,
+    // so concatenating every token and trivia text gives back the whole buffer.
+    pub fn ScanLossless(&mut self) -> Result<LosslessToken<'a>, LosslessEOF<'a>> {
+        let mut leading = self.ScanTrivia(false);
+
+        loop {
+            let begin = self.GetPos();
+            if self.PeekChar(0).is_none() {
+                return Err(LosslessEOF { Pos: begin, Leading: leading });
+            }
+
+            match self.Scan() {
+                Ok(tok) => {
+                    let text = self.Slice(tok.Pos);
+                    let trailing = self.ScanTrivia(true);
+                    return Ok(LosslessToken { Leading: leading, Token: tok, Text: text, Trailing: trailing });
+                }
+                Err(_) => {
+                    if self.GetPos().Offset == begin.Offset {
+                        let _ = self.Move();
+                    }
+                    self.PushTrivia(&mut leading, begin, TriviaKind::Skipped);
+                    leading.append(&mut self.ScanTrivia(false));
+                }
+            }
+        }
+    }
+}
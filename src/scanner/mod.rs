@@ -2,15 +2,28 @@
 // This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
+pub use incremental::*;
+pub use literal::*;
+pub use lossless::*;
 pub use pos::*;
 pub use scanner::*;
+pub use source::*;
 pub use token::*;
 
+pub mod incremental;
+
+pub mod literal;
+
+pub mod lossless;
+
 pub mod pos;
 
 pub mod scanner;
 
+pub mod source;
+
 pub mod token;
 
+#[cfg(test)]
 mod scanner_test;
 
@@ -2,23 +2,33 @@
 // This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 pub struct Position {
     pub Offset: usize,
     pub Line: usize,
     pub Column: usize,
 }
 
+// Line and Column count from 0, and are printed from 1.
 impl std::fmt::Display for Position {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "{:x}:{}:{}", self.Offset, self.Line, self.Column) }
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "{}:{}", self.Line + 1, self.Column + 1) }
 }
 
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 pub struct PosRange {
+    pub File: FileId,
     pub Begin: Position,
     pub End: Position,
 }
 
 impl std::fmt::Display for PosRange {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "{} -> {}", self.Begin, self.End) }
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "{}:{}", self.File, self.Begin) }
+}
+
+// Index of a file in the SourceMap.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct FileId(pub u32);
+
+impl std::fmt::Display for FileId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "#{}", self.0) }
 }
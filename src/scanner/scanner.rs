@@ -2,20 +2,30 @@
 // This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
+use std::borrow::Cow;
 use std::char::from_u32;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
 
-use err_rs::*;
+use unicode_ident::{is_xid_continue, is_xid_start};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+use unicode_security::{MixedScript, skeleton};
 
 use crate::scanner::*;
 
-pub struct BufferScanner {
+// Scans UTF-8 source in place: Offset counts bytes, Column counts chars.
+pub struct BufferScanner<'a> {
+    pub File: FileId,
     pub Pos: Position,
-    pub Buffer: Vec<char>,
+    pub Buffer: &'a str,
 }
 
 pub struct EOFError {
-    pub Pos: Position,
+    pub Pos: PosRange,
+}
+
+impl EOFError {
+    pub fn Render(&self, sm: &SourceMap) -> String { format!("{}: end of file", sm.Render(self.Pos)) }
 }
 
 impl Debug for EOFError {
@@ -24,17 +34,23 @@ impl Debug for EOFError {
     }
 }
 
-impl BufferScanner {
+impl<'a> BufferScanner<'a> {
     pub fn GetChar(&self) -> Result<char, EOFError> {
-        if self.Pos.Offset == self.Buffer.len() {
-            return Err(EOFError {
-                Pos: self.Pos,
-            });
+        match self.Buffer[self.Pos.Offset..].chars().next() {
+            Some(ch) => { Ok(ch) }
+            None => {
+                Err(EOFError {
+                    Pos: PosRange { File: self.File, Begin: self.Pos, End: self.Pos },
+                })
+            }
         }
-
-        Ok(self.Buffer[self.Pos.Offset])
     }
 
+    pub fn PeekChar(&self, n: usize) -> Option<char> { self.Buffer[self.Pos.Offset..].chars().nth(n) }
+
+    // Source text covered by the range, e.g. a literal before escape processing.
+    pub fn Slice(&self, pos: PosRange) -> &'a str { &self.Buffer[pos.Begin.Offset..pos.End.Offset] }
+
     pub fn Move(&mut self) -> Result<char, EOFError> {
         let ch = self.GetChar()?;
 
@@ -44,7 +60,7 @@ impl BufferScanner {
         } else {
             self.Pos.Column += 1;
         }
-        self.Pos.Offset += 1;
+        self.Pos.Offset += ch.len_utf8();
 
         Ok(ch)
     }
@@ -69,28 +85,130 @@ impl BufferScanner {
 pub enum BasicScannerError {
     EOF(EOFError),
     BadFormat(BadFormatError),
+    OutOfRange(OutOfRangeError),
+    UnterminatedString(UnterminatedStringError),
+    UnterminatedComment(UnterminatedCommentError),
+    BadEscape(BadEscapeError),
+    InvalidChar(InvalidCharError),
+    MalformedNumber(MalformedNumberError),
+    EmptyInterpolation(EmptyInterpolationError),
+}
+
+impl BasicScannerError {
+    pub fn Render(&self, sm: &SourceMap) -> String {
+        match self {
+            BasicScannerError::EOF(e) => { e.Render(sm) }
+            BasicScannerError::BadFormat(e) => { e.Render(sm) }
+            BasicScannerError::OutOfRange(e) => { e.Render(sm) }
+            BasicScannerError::UnterminatedString(e) => { e.Render(sm) }
+            BasicScannerError::UnterminatedComment(e) => { e.Render(sm) }
+            BasicScannerError::BadEscape(e) => { e.Render(sm) }
+            BasicScannerError::InvalidChar(e) => { e.Render(sm) }
+            BasicScannerError::MalformedNumber(e) => { e.Render(sm) }
+            BasicScannerError::EmptyInterpolation(e) => { e.Render(sm) }
+        }
+    }
+    pub fn PosRangeMut(&mut self) -> &mut PosRange {
+        match self {
+            BasicScannerError::EOF(e) => { &mut e.Pos }
+            BasicScannerError::BadFormat(e) => { &mut e.PosRange }
+            BasicScannerError::OutOfRange(e) => { &mut e.PosRange }
+            BasicScannerError::UnterminatedString(e) => { &mut e.PosRange }
+            BasicScannerError::UnterminatedComment(e) => { &mut e.PosRange }
+            BasicScannerError::BadEscape(e) => { &mut e.PosRange }
+            BasicScannerError::InvalidChar(e) => { &mut e.PosRange }
+            BasicScannerError::MalformedNumber(e) => { &mut e.PosRange }
+            BasicScannerError::EmptyInterpolation(e) => { &mut e.PosRange }
+        }
+    }
+}
+
+macro_rules! def_scanner_errors {
+    ($($name:ident $message:expr), *) => {
+        $(
+        pub struct $name {
+            pub PosRange: PosRange,
+        }
+
+        impl $name {
+            pub fn Render(&self, sm: &SourceMap) -> String { format!("{}: {}", sm.Render(self.PosRange), $message) }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}: {}", self.PosRange, $message)
+            }
+        }
+        )*
+    };
+}
+
+def_scanner_errors! {
+    BadFormatError              "format error",
+    OutOfRangeError             "integer literal out of range",
+    UnterminatedStringError     "unterminated string",
+    UnterminatedCommentError    "unterminated block comment",
+    BadEscapeError              "bad escape sequence",
+    InvalidCharError            "invalid character",
+    MalformedNumberError        "malformed number",
+    EmptyInterpolationError     "empty interpolation"
 }
 
-pub struct BadFormatError {
+#[derive(Debug)]
+pub enum BasicScannerWarning {
+    ConfusableIdent(ConfusableIdentWarning),
+    MixedScriptIdent(MixedScriptIdentWarning),
+}
+
+// Two distinct identifiers that look alike (UTS #39 skeletons are equal), one of them non-ASCII.
+pub struct ConfusableIdentWarning {
     pub PosRange: PosRange,
+    pub Ident: String,
+    pub Similar: String,
 }
 
-impl Debug for BadFormatError {
+impl Debug for ConfusableIdentWarning {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: format error", self.PosRange)
+        write!(f, "{}: identifier {} is confusable with {}", self.PosRange, self.Ident, self.Similar)
     }
 }
 
-pub struct BasicScanner {
-    pub BufferScanner: BufferScanner,
+pub struct MixedScriptIdentWarning {
+    pub PosRange: PosRange,
+    pub Ident: String,
+}
+
+impl Debug for MixedScriptIdentWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: identifier {} mixes scripts", self.PosRange, self.Ident)
+    }
+}
+
+pub struct BasicScanner<'a> {
+    pub BufferScanner: BufferScanner<'a>,
 
     pub Delimiters: Vec<char>,
     pub Whitespaces: Vec<char>,
+    // Operator spellings, longest first
+    pub Operators: Vec<&'static str>,
+
+    // Brace depth inside each open string interpolation, innermost last.
+    pub Interpolations: Vec<usize>,
+
+    // Skeleton of every identifier seen so far, mapped to its first spelling.
+    pub IdentSkeletons: HashMap<String, String>,
+    // ASCII identifiers already checked, borrowed from the buffer.
+    pub AsciiIdents: HashSet<&'a str>,
+
+    pub Warnings: Vec<BasicScannerWarning>,
+    // Lexical errors Scan recovered from, in source order
+    pub Errors: Vec<BasicScannerError>,
 }
 
-impl BufferScanner {
-    pub fn new(buffer: Vec<char>) -> BufferScanner {
+impl<'a> BufferScanner<'a> {
+    pub fn new(buffer: &'a str) -> BufferScanner<'a> {
         BufferScanner {
+            File: FileId::default(),
             Pos: Position {
                 Offset: 0,
                 Line: 0,
@@ -101,18 +219,44 @@ impl BufferScanner {
     }
 }
 
+impl<'a> BasicScanner<'a> {
+    pub fn new(buffer: &'a str, delimiters: Vec<char>, whitespaces: Vec<char>, operators: Vec<&'static str>) -> BasicScanner<'a> {
+        BasicScanner {
+            BufferScanner: BufferScanner::new(buffer),
+            Delimiters: delimiters,
+            Whitespaces: whitespaces,
+            Operators: operators,
+            Interpolations: vec![],
+            IdentSkeletons: HashMap::default(),
+            AsciiIdents: HashSet::default(),
+            Warnings: vec![],
+            Errors: vec![],
+        }
+    }
+}
+
+macro_rules! range {
+    ($begin: expr, $self: expr) => {
+        PosRange { File: $self.BufferScanner.File, Begin: $begin, End: $self.GetPos() }
+    };
+}
+
 macro_rules! from_to {
     ($begin: expr, $vec: expr) => {
-		$vec.BufferScanner.Buffer[$begin.Offset..$vec.GetPos().Offset].to_owned()
+		Cow::Borrowed(&$vec.BufferScanner.Buffer[$begin.Offset..$vec.GetPos().Offset])
 	};
 }
 
-impl BasicScanner {
-    pub fn GetChar(&self) -> Result<char, BasicScannerError> { Ok(wrap_result!(BasicScannerError::EOF, self.BufferScanner.GetChar())) }
+impl<'a> BasicScanner<'a> {
+    pub fn GetChar(&self) -> Result<char, BasicScannerError> { self.BufferScanner.GetChar().map_err(BasicScannerError::EOF) }
 
-    pub fn Move(&mut self) -> Result<char, BasicScannerError> { Ok(wrap_result!(BasicScannerError::EOF, self.BufferScanner.Move())) }
+    pub fn PeekChar(&self, n: usize) -> Option<char> { self.BufferScanner.PeekChar(n) }
 
-    pub fn GotoNextLine(&mut self) -> Result<(), BasicScannerError> { Ok(wrap_result!(BasicScannerError::EOF, self.BufferScanner.GotoNextLine())) }
+    pub fn Slice(&self, pos: PosRange) -> &'a str { self.BufferScanner.Slice(pos) }
+
+    pub fn Move(&mut self) -> Result<char, BasicScannerError> { self.BufferScanner.Move().map_err(BasicScannerError::EOF) }
+
+    pub fn GotoNextLine(&mut self) -> Result<(), BasicScannerError> { self.BufferScanner.GotoNextLine().map_err(BasicScannerError::EOF) }
 
     pub fn GetPos(&self) -> Position { self.BufferScanner.Pos }
 
@@ -124,203 +268,394 @@ impl BasicScanner {
         Ok(())
     }
 
-    pub fn ScanLineComment(&mut self) -> Result<BasicToken, BasicScannerError> {
-        let begin = self.GetPos();
+    // Scans a line comment up to, not including, the newline. `///` starts a doc comment, `////` does not.
+    pub fn ScanLineComment(&mut self, begin: Position) -> Result<BasicToken<'a>, BasicScannerError> {
+        let doc = self.PeekChar(0) == Some('/') && self.PeekChar(1) != Some('/');
+        if doc {
+            self.Move()?;
+        }
 
-        self.GotoNextLine()?;
+        let textBegin = self.GetPos();
+
+        while let Some(ch) = self.PeekChar(0) {
+            if ch == '\n' {
+                break;
+            }
+            self.Move()?;
+        }
 
         Ok(BasicToken {
-            Pos: PosRange { Begin: begin, End: self.GetPos() },
-            Kind: BasicTokenKind::Comment,
-            Literal: from_to!(begin, self),
+            Pos: range!(begin, self),
+            Kind: if doc { BasicTokenKind::DocComment } else { BasicTokenKind::Comment },
+            Literal: from_to!(textBegin, self),
         })
     }
 
-    pub fn ScanQuotedComment(&mut self) -> Result<BasicToken, BasicScannerError> {
-        let begin = self.GetPos();
+    // Scans a block comment, which nests: `/* a /* b */ c */` is one comment.
+    // `/** */` starts a doc comment, `/**/` is an empty ordinary comment.
+    pub fn ScanQuotedComment(&mut self, begin: Position) -> Result<BasicToken<'a>, BasicScannerError> {
+        let doc = self.PeekChar(0) == Some('*') && self.PeekChar(1) != Some('/');
+        if doc {
+            self.Move()?;
+        }
 
-        loop {
-            if self.Move()? == '*' {
-                if self.Move()? == '/' {
-                    break;
+        let textBegin = self.GetPos();
+        let mut textEnd = textBegin;
+
+        let mut depth = 1;
+        while depth != 0 {
+            textEnd = self.GetPos();
+            let ch = match self.Move() {
+                Ok(ch) => { ch }
+                Err(_) => { return Err(BasicScannerError::UnterminatedComment(UnterminatedCommentError { PosRange: range!(begin, self) })); }
+            };
+            match (ch, self.PeekChar(0)) {
+                ('/', Some('*')) => {
+                    self.Move()?;
+                    depth += 1;
+                }
+                ('*', Some('/')) => {
+                    self.Move()?;
+                    depth -= 1;
                 }
+                _ => {}
             }
         }
 
-
         Ok(BasicToken {
-            Pos: PosRange { Begin: begin, End: self.GetPos() },
-            Kind: BasicTokenKind::Comment,
-            Literal: from_to!(begin, self),
+            Pos: range!(begin, self),
+            Kind: if doc { BasicTokenKind::DocComment } else { BasicTokenKind::Comment },
+            Literal: Cow::Borrowed(&self.BufferScanner.Buffer[textBegin.Offset..textEnd.Offset]),
         })
     }
 
-    pub fn ScanComment(&mut self) -> Result<BasicToken, BasicScannerError> {
+    pub fn ScanComment(&mut self) -> Result<BasicToken<'a>, BasicScannerError> {
         let begin = self.GetPos();
 
+        self.Move()?; // skip '/'
+
         return match self.Move()? {
-            '/' => { self.ScanLineComment() }
-            '*' => { self.ScanQuotedComment() }
+            '/' => { self.ScanLineComment(begin) }
+            '*' => { self.ScanQuotedComment(begin) }
             _ => {
                 return Err(BasicScannerError::BadFormat(BadFormatError {
-                    PosRange: PosRange { Begin: begin, End: self.GetPos() },
+                    PosRange: range!(begin, self),
                 }));
             }
         };
     }
 
-    pub fn ScanIdent(&mut self) -> Result<BasicToken, BasicScannerError> {
+    // Scans an identifier following UAX #31: XID_Start or '_', then XID_Continue characters.
+    // The literal is NFC-normalized so that canonically equivalent spellings are the same identifier.
+    pub fn ScanIdent(&mut self) -> Result<BasicToken<'a>, BasicScannerError> {
         let begin = self.GetPos();
 
-        loop {
-            let ch = self.GetChar()?;
-            if ch.is_ascii_alphabetic() || ch.is_numeric() || ch == '_' {
-                self.Move()?;
-            } else {
+        self.Move()?;
+
+        while let Some(ch) = self.PeekChar(0) {
+            if !is_xid_continue(ch) {
                 break;
             }
+            self.Move()?;
+        }
+
+        let pos = range!(begin, self);
+        let literal = match from_to!(begin, self) {
+            Cow::Borrowed(it) if !is_nfc(it) => { Cow::Owned(it.nfc().collect()) }
+            it => { it }
+        };
+
+        match literal {
+            // computing skeletons is costly, check each ASCII spelling once
+            Cow::Borrowed(it) if it.is_ascii() => {
+                if self.AsciiIdents.insert(it) {
+                    self.CheckConfusable(pos, it);
+                }
+            }
+            _ => { self.CheckConfusable(pos, &literal); }
         }
 
         Ok(BasicToken {
-            Pos: PosRange { Begin: begin, End: self.GetPos() },
+            Pos: pos,
             Kind: BasicTokenKind::Ident,
-            Literal: from_to!(begin, self),
+            Literal: literal,
         })
     }
 
-    pub fn ScanHex(&mut self) -> Result<BasicToken, BasicScannerError> {
-        let begin = self.GetPos();
+    fn CheckConfusable(&mut self, pos: PosRange, ident: &str) {
+        if !ident.is_ascii() && !ident.is_single_script() {
+            self.Warnings.push(BasicScannerWarning::MixedScriptIdent(MixedScriptIdentWarning { PosRange: pos, Ident: ident.to_owned() }));
+        }
+
+        let key: String = skeleton(ident).collect();
+        match self.IdentSkeletons.get(&key) {
+            Some(similar) if similar != ident => {
+                if !ident.is_ascii() || !similar.is_ascii() {
+                    self.Warnings.push(BasicScannerWarning::ConfusableIdent(ConfusableIdentWarning {
+                        PosRange: pos,
+                        Ident: ident.to_owned(),
+                        Similar: similar.clone(),
+                    }));
+                }
+            }
+            Some(_) => {}
+            None => { self.IdentSkeletons.insert(key, ident.to_owned()); }
+        }
+    }
+
+    // Scans digits with `_` separators between them. A separator must follow a digit and be followed by one;
+    // the digits after a bad separator are still scanned, so the error leaves no half number behind.
+    pub fn ScanDigits(&mut self, radix: u32) -> Result<usize, BasicScannerError> {
+        let mut digitsN = 0;
+        // Beginning and length of the run of separators since the last digit
+        let mut separators: Option<(Position, usize)> = None;
+        let mut bad: Option<PosRange> = None;
 
         loop {
-            let ch = self.GetChar()?;
-            if '0' <= ch && ch <= '9' || 'a' <= ch && ch <= 'f' {
-                self.Move()?;
-            } else {
+            match self.PeekChar(0) {
+                Some(ch) if ch.is_digit(radix) => {
+                    if let Some((begin, n)) = separators.take() {
+                        if n > 1 {
+                            bad.get_or_insert(range!(begin, self)); // consecutive
+                        }
+                    }
+                    digitsN += 1;
+                }
+                Some('_') if digitsN != 0 => {
+                    let pos = self.GetPos();
+                    separators.get_or_insert((pos, 0)).1 += 1;
+                }
+                _ => { break; }
+            }
+            self.Move()?;
+        }
+
+        if let Some((begin, _)) = separators {
+            bad.get_or_insert(range!(begin, self)); // trailing
+        }
+
+        match bad {
+            Some(pos) => { Err(BasicScannerError::MalformedNumber(MalformedNumberError { PosRange: pos })) }
+            None => { Ok(digitsN) }
+        }
+    }
+
+    // Scans the exponent of a float literal: 'e'/'E' for decimal and 'p'/'P' for hex mantissas,
+    // followed by an optional sign and at least one decimal digit.
+    pub fn ScanExponent(&mut self) -> Result<(), BasicScannerError> {
+        let begin = self.GetPos();
+
+        self.Move()?; // skip exponent mark
+
+        match self.PeekChar(0) {
+            Some('+') | Some('-') => { self.Move()?; }
+            _ => {}
+        }
+
+        if self.ScanDigits(10)? == 0 {
+            return Err(BasicScannerError::MalformedNumber(MalformedNumberError {
+                PosRange: range!(begin, self),
+            }));
+        }
+
+        Ok(())
+    }
+
+    // Scans the type suffix directly following a number literal, e.g. `u8`, `i64` or `f32`.
+    // Float suffixes also apply to decimal integers, which makes `1f32` a float; returns whether the literal is a float.
+    pub fn ScanNumberSuffix(&mut self, float: bool, decimal: bool) -> Result<bool, BasicScannerError> {
+        let begin = self.GetPos();
+
+        while let Some(ch) = self.PeekChar(0) {
+            if !is_xid_continue(ch) {
                 break;
             }
+            self.Move()?;
         }
 
+        let suffix = from_to!(begin, self);
+
+        match suffix.as_ref() {
+            "" => { Ok(float) }
+            "f32" | "f64" if float || decimal => { Ok(true) }
+            _ if !float && IntSuffix::Lookup(&suffix).is_some() => { Ok(false) }
+            _ => {
+                Err(BasicScannerError::MalformedNumber(MalformedNumberError {
+                    PosRange: range!(begin, self),
+                }))
+            }
+        }
+    }
+
+    pub fn ScanHex(&mut self) -> Result<BasicToken<'a>, BasicScannerError> {
+        let begin = self.GetPos();
+
+        let mut digitsN = self.ScanDigits(16)?;
+        let mut float = false;
+
+        if self.PeekChar(0) == Some('.') {
+            match self.PeekChar(1) {
+                Some(ch) if ch.is_digit(16) || ch == 'p' || ch == 'P' => {
+                    self.Move()?;
+                    digitsN += self.ScanDigits(16)?;
+                    float = true;
+                }
+                _ => {}
+            }
+        }
+
+        if digitsN == 0 {
+            return Err(BasicScannerError::MalformedNumber(MalformedNumberError {
+                PosRange: range!(begin, self),
+            }));
+        }
+
+        match self.PeekChar(0) {
+            Some('p') | Some('P') => {
+                self.ScanExponent()?;
+                float = true;
+            }
+            _ if float => { // hex mantissa requires an exponent
+                return Err(BasicScannerError::MalformedNumber(MalformedNumberError {
+                    PosRange: range!(begin, self),
+                }));
+            }
+            _ => {}
+        }
+
+        float = self.ScanNumberSuffix(float, false)?;
+
         Ok(BasicToken {
-            Pos: PosRange { Begin: begin, End: self.GetPos() },
-            Kind: BasicTokenKind::Int(IntFormat::HEX),
+            Pos: range!(begin, self),
+            Kind: if float { BasicTokenKind::Float(IntFormat::HEX) } else { BasicTokenKind::Int(IntFormat::HEX) },
             Literal: from_to!(begin, self),
         })
     }
 
-    pub fn ScanDec(&mut self) -> Result<BasicToken, BasicScannerError> {
+    pub fn ScanDec(&mut self) -> Result<BasicToken<'a>, BasicScannerError> {
         let begin = self.GetPos();
 
-        loop {
-            let ch = self.GetChar()?;
-            if '0' <= ch && ch <= '9' {
-                self.Move()?;
-            } else {
-                break;
+        self.ScanDigits(10)?;
+
+        let mut float = false;
+
+        // The dot starts a fraction only when followed by a digit or an exponent with its digit or sign,
+        // so `1..2`, `1.Method()` and `t.0.Elem` keep scanning as integers.
+        if self.PeekChar(0) == Some('.') {
+            let exponent = matches!(self.PeekChar(1), Some('e') | Some('E'))
+                && self.PeekChar(2).map_or(false, |ch| ch.is_ascii_digit() || ch == '+' || ch == '-');
+            match self.PeekChar(1) {
+                Some(ch) if ch.is_ascii_digit() || exponent => {
+                    self.Move()?;
+                    self.ScanDigits(10)?;
+                    float = true;
+                }
+                _ => {}
             }
         }
 
+        match self.PeekChar(0) {
+            Some('e') | Some('E') => {
+                self.ScanExponent()?;
+                float = true;
+            }
+            _ => {}
+        }
+
+        float = self.ScanNumberSuffix(float, true)?;
 
         Ok(BasicToken {
-            Pos: PosRange { Begin: begin, End: self.GetPos() },
-            Kind: BasicTokenKind::Int(IntFormat::DEC),
+            Pos: range!(begin, self),
+            Kind: if float { BasicTokenKind::Float(IntFormat::DEC) } else { BasicTokenKind::Int(IntFormat::DEC) },
             Literal: from_to!(begin, self),
         })
     }
 
-    pub fn ScanOct(&mut self) -> Result<BasicToken, BasicScannerError> {
+    pub fn ScanOct(&mut self) -> Result<BasicToken<'a>, BasicScannerError> {
         let begin = self.GetPos();
 
-        loop {
-            let ch = self.GetChar()?;
-            if '0' <= ch && ch <= '7' {
-                self.Move()?;
-            } else {
-                break;
-            }
+        if self.ScanDigits(8)? == 0 {
+            return Err(BasicScannerError::MalformedNumber(MalformedNumberError {
+                PosRange: range!(begin, self),
+            }));
         }
 
+        self.ScanNumberSuffix(false, false)?;
 
         Ok(BasicToken {
-            Pos: PosRange { Begin: begin, End: self.GetPos() },
+            Pos: range!(begin, self),
             Kind: BasicTokenKind::Int(IntFormat::OCT),
             Literal: from_to!(begin, self),
         })
     }
 
-    pub fn ScanBin(&mut self) -> Result<BasicToken, BasicScannerError> {
+    pub fn ScanBin(&mut self) -> Result<BasicToken<'a>, BasicScannerError> {
         let begin = self.GetPos();
 
-        loop {
-            let ch = self.GetChar()?;
-            if ch == '0' || ch == '1' {
-                self.Move()?;
-            } else {
-                break;
-            }
+        if self.ScanDigits(2)? == 0 {
+            return Err(BasicScannerError::MalformedNumber(MalformedNumberError {
+                PosRange: range!(begin, self),
+            }));
         }
 
+        self.ScanNumberSuffix(false, false)?;
 
         Ok(BasicToken {
-            Pos: PosRange { Begin: begin, End: self.GetPos() },
+            Pos: range!(begin, self),
             Kind: BasicTokenKind::Int(IntFormat::BIN),
             Literal: from_to!(begin, self),
         })
     }
 
-    pub fn ScanDigit(&mut self) -> Result<BasicToken, BasicScannerError> {
+    pub fn ScanDigit(&mut self) -> Result<BasicToken<'a>, BasicScannerError> {
         let begin = self.GetPos();
 
-        match self.Move()? {
-            '0' => {
-                match self.Move()? {
-                    'x' => { self.ScanHex() }
-                    'o' => { self.ScanOct() }
-                    'b' => { self.ScanBin() }
-                    _ => {
-                        return Err(BasicScannerError::BadFormat(BadFormatError {
-                            PosRange: PosRange {
-                                Begin: begin,
-                                End: self.GetPos(),
-                            },
-                        }));
-                    }
-                }
+        let mut tok = match (self.GetChar()?, self.PeekChar(1)) {
+            ('0', Some('x')) => {
+                self.Move()?;
+                self.Move()?;
+                self.ScanHex()?
             }
-            _ => {
-                self.BufferScanner.Pos = begin;
-                self.ScanDec()
+            ('0', Some('o')) => {
+                self.Move()?;
+                self.Move()?;
+                self.ScanOct()?
+            }
+            ('0', Some('b')) => {
+                self.Move()?;
+                self.Move()?;
+                self.ScanBin()?
+            }
+            _ => { self.ScanDec()? }
+        };
+        tok.Pos.Begin = begin; // the token covers the radix prefix, its literal does not
+
+        if let BasicTokenKind::Int(format) = tok.Kind {
+            // too large for u128, or for the type its suffix names
+            if DecodeInt(format, &tok.Literal).is_none() {
+                return Err(BasicScannerError::OutOfRange(OutOfRangeError { PosRange: tok.Pos }));
             }
         }
+
+        Ok(tok)
     }
 
+    // Scans exactly `runesN` hex digits naming a Unicode scalar. Only hex digits are consumed,
+    // so a short escape does not swallow the closing quote.
     pub fn ScanUnicodeHex(&mut self, runesN: u8) -> Result<char, BasicScannerError> {
         let begin = self.GetPos();
 
-        let mut seq: Vec<char> = vec![];
         for _ in 0..runesN {
-            seq.push(self.Move()?);
-        }
-
-        let ch = match u32::from_str_radix(&String::from_iter(seq), 16) {
-            Ok(ch) => {
-                match from_u32(ch) {
-                    None => {
-                        return Err(BasicScannerError::BadFormat(BadFormatError {
-                            PosRange: PosRange { Begin: begin, End: self.GetPos() }
-                        }));
-                    }
-                    Some(ch) => { ch }
-                }
-            }
-            Err(err) => {
-                println!("{}", err.to_string());
-                return Err(BasicScannerError::BadFormat(BadFormatError {
-                    PosRange: PosRange { Begin: begin, End: self.GetPos() },
-                }));
+            match self.PeekChar(0) {
+                Some(ch) if ch.is_ascii_hexdigit() => { self.Move()?; }
+                _ => { return Err(BasicScannerError::BadEscape(BadEscapeError { PosRange: range!(begin, self) })); }
             }
-        };
+        }
 
-        Ok(ch)
+        match u32::from_str_radix(&from_to!(begin, self), 16).ok().and_then(from_u32) {
+            Some(ch) => { Ok(ch) }
+            None => { Err(BasicScannerError::BadEscape(BadEscapeError { PosRange: range!(begin, self) })) }
+        }
     }
 
     pub fn ScanEscapeChar(&mut self, quote: char) -> Result<char, BasicScannerError> {
@@ -342,81 +677,374 @@ impl BasicScanner {
             'U' => { // 4 byte
                 self.ScanUnicodeHex(8)?
             }
+            '{' => { '{' }
+            '}' => { '}' }
             _ if ch == quote => { quote }
             _ => {
-                return Err(BasicScannerError::BadFormat(BadFormatError { PosRange: PosRange { Begin: begin, End: self.GetPos() } }));
+                return Err(BasicScannerError::BadEscape(BadEscapeError { PosRange: range!(begin, self) }));
             }
         })
     }
 
-    pub fn ScanString(&mut self, quote: char) -> Result<BasicToken, BasicScannerError> {
+    // The next char that is not whitespace, without moving.
+    fn PeekNonWhitespace(&self) -> Option<char> {
+        let mut i = 0;
+        while let Some(ch) = self.PeekChar(i) {
+            if !self.Whitespaces.contains(&ch) {
+                return Some(ch);
+            }
+            i += 1;
+        }
+        None
+    }
+
+    // Moves inside a string or char literal starting at `begin`, where the end of file means it is unterminated.
+    fn MoveInString(&mut self, begin: Position) -> Result<char, BasicScannerError> {
+        match self.Move() {
+            Ok(ch) => { Ok(ch) }
+            Err(_) => { Err(BasicScannerError::UnterminatedString(UnterminatedStringError { PosRange: range!(begin, self) })) }
+        }
+    }
+
+    pub fn ScanString(&mut self, quote: char) -> Result<BasicToken<'a>, BasicScannerError> {
         let begin = self.GetPos();
 
         self.Move()?; // skip quote
 
-        let mut seq: Vec<char> = vec![];
+        self.ScanStringSegment(begin, quote, true)
+    }
 
-        loop {
-            let ch = self.Move()?;
+    // Scans string content up to the closing quote or up to a `{` opening an interpolated expression.
+    // An interpolated literal `"a{x}b{y}c"` is split into StringHead `a`, the tokens of `x`, StringMiddle `b`,
+    // the tokens of `y` and StringTail `c`; a literal without interpolation stays a single String.
+    // A bad escape does not stop the segment: the rest is scanned so that the error covers the whole literal.
+    pub fn ScanStringSegment(&mut self, begin: Position, quote: char, head: bool) -> Result<BasicToken<'a>, BasicScannerError> {
+        let mut seq = String::new();
+        let mut bad: Option<BasicScannerError> = None;
+
+        let kind = loop {
+            // a quoted string ends on its line, the newline is left for the next token
+            if self.PeekChar(0) == Some('\n') {
+                return Err(BasicScannerError::UnterminatedString(UnterminatedStringError { PosRange: range!(begin, self) }));
+            }
+            let chBegin = self.GetPos();
+            let ch = self.MoveInString(begin)?;
             match ch {
                 '\\' => {
-                    let esc = self.ScanEscapeChar(quote)?;
-                    seq.push(esc)
+                    match self.ScanEscapeChar(quote) {
+                        Ok(esc) => { seq.push(esc) }
+                        Err(e) => { bad.get_or_insert(e); }
+                    }
+                }
+                // `{}` holds no expression, the error covers the whole literal
+                '{' if self.PeekNonWhitespace() == Some('}') => {
+                    while self.MoveInString(begin)? != '}' {}
+                    bad.get_or_insert(BasicScannerError::EmptyInterpolation(EmptyInterpolationError { PosRange: range!(chBegin, self) }));
+                }
+                '{' => {
+                    self.Interpolations.push(0);
+                    break if head { BasicTokenKind::StringHead } else { BasicTokenKind::StringMiddle };
                 }
                 _ if ch == quote => {
-                    break;
+                    break if head { BasicTokenKind::String } else { BasicTokenKind::StringTail };
                 }
                 _ => { seq.push(ch) }
             }
+        };
+
+        if let Some(e) = bad {
+            return Err(e);
+        }
+
+        Ok(BasicToken {
+            Pos: range!(begin, self),
+            Kind: kind,
+            Literal: Cow::Owned(seq),
+        })
+    }
+
+    // Scans a raw string `r"..."` or `r#"..."#`: no escape processing, may span lines,
+    // and the number of '#' on both sides must match so the content can hold quotes.
+    pub fn ScanRawString(&mut self) -> Result<BasicToken<'a>, BasicScannerError> {
+        let begin = self.GetPos();
+
+        self.Move()?; // skip 'r'
+
+        let mut hashesN = 0;
+        while self.GetChar()? == '#' {
+            self.Move()?;
+            hashesN += 1;
+        }
+
+        if self.MoveInString(begin)? != '"' {
+            return Err(BasicScannerError::BadFormat(BadFormatError {
+                PosRange: range!(begin, self),
+            }));
+        }
+
+        let mut seq = String::new();
+
+        loop {
+            let ch = self.MoveInString(begin)?;
+            if ch == '"' && (0..hashesN).all(|i| self.PeekChar(i) == Some('#')) {
+                for _ in 0..hashesN {
+                    self.Move()?;
+                }
+                break;
+            }
+            seq.push(ch);
+        }
+
+        Ok(BasicToken {
+            Pos: range!(begin, self),
+            Kind: BasicTokenKind::String,
+            Literal: Cow::Owned(seq),
+        })
+    }
+
+    // Finds the closing `"""` of a block string starting at the current line,
+    // and returns the whitespace preceding it, which is the indentation stripped from every line.
+    fn BlockStringIndent(&self) -> Option<&'a str> {
+        let buffer = self.BufferScanner.Buffer.as_bytes();
+
+        let mut offset = self.GetPos().Offset;
+        loop {
+            let lineBegin = offset;
+            while offset < buffer.len() && (buffer[offset] == b' ' || buffer[offset] == b'\t') {
+                offset += 1;
+            }
+            if buffer[offset..].starts_with(b"\"\"\"") {
+                return Some(&self.BufferScanner.Buffer[lineBegin..offset]);
+            }
+            while offset < buffer.len() && buffer[offset] != b'\n' {
+                offset += 1;
+            }
+            if offset == buffer.len() {
+                return None;
+            }
+            offset += 1;
+        }
+    }
+
+    // Scans a multi-line block string. The opening `"""` ends its line and the closing `"""` sits on its own line;
+    // the closing line's indentation is stripped from each content line, and escapes are processed as in ScanString.
+    pub fn ScanBlockString(&mut self) -> Result<BasicToken<'a>, BasicScannerError> {
+        let begin = self.GetPos();
+
+        for _ in 0..3 {
+            self.Move()?; // skip quotes
+        }
+
+        if self.MoveInString(begin)? != '\n' {
+            return Err(BasicScannerError::BadFormat(BadFormatError {
+                PosRange: range!(begin, self),
+            }));
+        }
+
+        let indent = match self.BlockStringIndent() {
+            Some(indent) => { indent }
+            None => {
+                while self.Move().is_ok() {}
+                return Err(BasicScannerError::UnterminatedString(UnterminatedStringError { PosRange: range!(begin, self) }));
+            }
+        };
+
+        let mut seq = String::new();
+        let mut bad: Option<BasicScannerError> = None;
+
+        'lines: loop {
+            let lineBegin = self.GetPos();
+
+            for ch in indent.chars() {
+                match self.GetChar()? {
+                    '\n' => { break; } // blank lines may be shorter than the indentation
+                    it if it == ch => { self.Move()?; }
+                    _ => { // keep going to the closing quotes, the error covers the whole literal
+                        bad.get_or_insert(BasicScannerError::BadFormat(BadFormatError {
+                            PosRange: range!(lineBegin, self),
+                        }));
+                        break;
+                    }
+                }
+            }
+
+            if (0..3).all(|i| self.PeekChar(i) == Some('"')) {
+                for _ in 0..3 {
+                    self.Move()?;
+                }
+                break 'lines;
+            }
+
+            loop {
+                let ch = self.Move()?;
+                match ch {
+                    '\\' => {
+                        match self.ScanEscapeChar('"') {
+                            Ok(esc) => { seq.push(esc) }
+                            Err(e) => { bad.get_or_insert(e); }
+                        }
+                    }
+                    '\n' => {
+                        seq.push(ch);
+                        break;
+                    }
+                    _ => { seq.push(ch) }
+                }
+            }
         }
 
+        if let Some(e) = bad {
+            return Err(e);
+        }
+
+        seq.pop(); // the newline before closing quotes
+
         Ok(BasicToken {
-            Pos: PosRange { Begin: begin, End: self.GetPos() },
+            Pos: range!(begin, self),
             Kind: BasicTokenKind::String,
-            Literal: seq,
+            Literal: Cow::Owned(seq),
         })
     }
 
-    pub fn ScanOperator(&mut self) -> Result<BasicToken, BasicScannerError> {
+    // Scans a character literal, which must hold exactly one Unicode scalar after escape processing.
+    pub fn ScanChar(&mut self) -> Result<BasicToken<'a>, BasicScannerError> {
         let begin = self.GetPos();
 
+        self.Move()?; // skip quote
+
+        let mut seq = String::new();
+        let mut bad: Option<BasicScannerError> = None;
+
         loop {
-            match self.GetChar()? {
-                '"' => { break; }
-                '\'' => { break; }
-                ch if !ch.is_ascii_punctuation() => { break; }
-                ch if self.Delimiters.contains(&ch) => { break; }
-                _ => { self.Move()?; }
+            // like a quoted string, a char ends on its line and leaves the newline for the next token
+            if self.PeekChar(0) == Some('\n') {
+                return Err(BasicScannerError::UnterminatedString(UnterminatedStringError { PosRange: range!(begin, self) }));
+            }
+            let ch = self.MoveInString(begin)?;
+            match ch {
+                '\\' => {
+                    match self.ScanEscapeChar('\'') {
+                        Ok(esc) => { seq.push(esc) }
+                        Err(e) => { bad.get_or_insert(e); }
+                    }
+                }
+                '\'' => {
+                    break;
+                }
+                _ => { seq.push(ch) }
             }
         }
 
+        if let Some(e) = bad {
+            return Err(e);
+        }
+
+        if seq.chars().count() != 1 {
+            return Err(BasicScannerError::BadFormat(BadFormatError {
+                PosRange: range!(begin, self),
+            }));
+        }
+
         Ok(BasicToken {
-            Pos: PosRange { Begin: begin, End: self.GetPos() },
+            Pos: range!(begin, self),
+            Kind: BasicTokenKind::Char,
+            Literal: Cow::Owned(seq),
+        })
+    }
+
+    // Scans the longest declared operator at the current position, e.g. `a+-b` splits into `+` `-`.
+    // Punctuation that starts no declared operator is an invalid character.
+    pub fn ScanOperator(&mut self) -> Result<BasicToken<'a>, BasicScannerError> {
+        let begin = self.GetPos();
+
+        let rest = &self.BufferScanner.Buffer[begin.Offset..];
+        match self.Operators.iter().find(|it| rest.starts_with(**it)) {
+            Some(op) => {
+                for _ in 0..op.len() {
+                    self.Move()?;
+                }
+            }
+            // punctuation that starts no declared operator, such as `@` or `$`
+            None => {
+                self.Move()?;
+                return Err(BasicScannerError::InvalidChar(InvalidCharError { PosRange: range!(begin, self) }));
+            }
+        }
+
+        Ok(BasicToken {
+            Pos: range!(begin, self),
             Kind: BasicTokenKind::Operator,
             Literal: from_to!(begin, self),
         })
     }
 
-    pub fn Scan(&mut self) -> Result<BasicToken, BasicScannerError> {
+    // Scans the next token. A lexical error is recorded in Errors and the source it covers
+    // becomes an Invalid token, so scanning goes on; the only error returned is the end of file.
+    pub fn Scan(&mut self) -> Result<BasicToken<'a>, BasicScannerError> {
+        self.SkipWhitespaces()?;
+
+        let begin = self.GetPos();
+
+        match self.ScanToken() {
+            Ok(tok) => { Ok(tok) }
+            Err(e) => {
+                self.Errors.push(e);
+                if self.GetPos().Offset == begin.Offset {
+                    self.Move()?;
+                }
+                Ok(BasicToken {
+                    Pos: range!(begin, self),
+                    Kind: BasicTokenKind::Invalid,
+                    Literal: from_to!(begin, self),
+                })
+            }
+        }
+    }
+
+    // Scans the next token, failing on malformed input.
+    pub fn ScanToken(&mut self) -> Result<BasicToken<'a>, BasicScannerError> {
         self.SkipWhitespaces()?;
 
         let begin = self.GetPos();
 
         match self.GetChar()? {
-            ch if ch.is_alphabetic() => { self.ScanIdent() }
-            ch if ch.is_numeric() => { self.ScanDigit() }
+            'r' if self.PeekChar(1) == Some('"') || self.PeekChar(1) == Some('#') => { self.ScanRawString() }
+            ch if ch == '_' || is_xid_start(ch) => { self.ScanIdent() }
+            ch if ch.is_ascii_digit() => { self.ScanDigit() }
+            '/' if self.PeekChar(1) == Some('/') || self.PeekChar(1) == Some('*') => { self.ScanComment() }
+            '}' if self.Interpolations.last() == Some(&0) => { // end of interpolated expression
+                self.Interpolations.pop();
+                self.Move()?;
+                self.ScanStringSegment(begin, '"', false)
+            }
             ch if self.Delimiters.contains(&ch) => {
+                match (ch, self.Interpolations.last_mut()) {
+                    ('{', Some(depth)) => { *depth += 1; }
+                    ('}', Some(depth)) => { *depth -= 1; }
+                    _ => {}
+                }
+                self.Move()?;
                 Ok(BasicToken {
-                    Pos: PosRange { Begin: begin, End: self.GetPos() },
+                    Pos: range!(begin, self),
                     Kind: BasicTokenKind::Delimiter,
-                    Literal: vec![self.Move()?],
+                    Literal: from_to!(begin, self),
                 })
             }
+            '"' if self.PeekChar(1) == Some('"') && self.PeekChar(2) == Some('"') => { self.ScanBlockString() }
             '"' => { self.ScanString('"') }
-            '\'' => { self.ScanString('\'') } // TODO
-            '/' => { self.ScanComment() }
+            '\'' => { self.ScanChar() }
             ch if ch.is_ascii_punctuation() => { self.ScanOperator() }
-            _ => { Err(BasicScannerError::EOF(EOFError { Pos: self.GetPos() })) }
+            _ => {
+                self.Move()?;
+                Err(BasicScannerError::InvalidChar(InvalidCharError { PosRange: range!(begin, self) }))
+            }
         }
     }
 }
+
+// Tokens up to the end of file; lexical errors are collected in Errors.
+impl<'a> Iterator for BasicScanner<'a> {
+    type Item = BasicToken<'a>;
+
+    fn next(&mut self) -> Option<BasicToken<'a>> { self.Scan().ok() }
+}
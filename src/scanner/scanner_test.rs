@@ -2,6 +2,8 @@
 // This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
+use proptest::prelude::*;
+
 use crate::scanner::*;
 
 /*
@@ -18,18 +20,11 @@ if i != 127 | 0b01 && i == '1' {
 fn TestScanner() {
     let src = r#"package main	var i = len("String for testing."+"")	i++	if i != 127 | 0b01 && i == '1' {		println("String for testing.\nChinese letter: \u554a")	}"#;
 
-    let mut s = BasicScanner {
-        BufferScanner: BufferScanner {
-            Pos: Position { Offset: 0, Line: 0, Column: 0 },
-            Buffer: src.chars().collect(),
-        },
-        Delimiters: vec![',', '(', ')', '[', ']', '{', '}'],
-        Whitespaces: vec![' ', '\r', '\t'],
-    };
+    let mut s = NewScanner(src);
     loop {
         match s.Scan() {
             Ok(tok) => {
-                println!("{}\n{}", tok.Pos.to_string(), tok.Literal.iter().collect::<String>())
+                println!("{}\n{}", tok.Pos.to_string(), tok.Literal)
             }
             Err(err) => {
                 println!("{:?}", err);
@@ -38,3 +33,370 @@ fn TestScanner() {
         };
     }
 }
+
+fn NewScanner(src: &str) -> BasicScanner {
+    BasicScanner::new(src, vec![',', '(', ')', '[', ']', '{', '}'], vec![' ', '\r', '\t'], vec!["<<=", "!=", "&&", "<-", "<<", "+", "-", "!", "&", "<", "="])
+}
+
+#[test]
+fn TestScanner_Float() {
+    for (src, literal) in [
+        ("3.14", "3.14"),
+        ("1e-9", "1e-9"),
+        ("6.02E23", "6.02E23"),
+        ("1_000.000_1", "1_000.000_1"),
+        ("2.5f32", "2.5f32"),
+        ("1f64", "1f64"),
+        ("0.5", "0.5"),
+        ("0x1.8p3", "1.8p3"),
+        ("0x1P-2", "1P-2"),
+    ] {
+        let tok = NewScanner(src).Scan().unwrap();
+        assert!(matches!(tok.Kind, BasicTokenKind::Float(_)), "{}", src);
+        assert_eq!(tok.Literal, literal);
+    }
+
+    for (src, literal) in [("1..2", "1"), ("0x1f32", "1f32"), ("0", "0"), ("1.exp()", "1"), ("0.Elem", "0"), ("0.e", "0")] {
+        let tok = NewScanner(src).Scan().unwrap();
+        assert!(matches!(tok.Kind, BasicTokenKind::Int(_)), "{}", src);
+        assert_eq!(tok.Literal, literal);
+    }
+
+    for (src, begin, end) in [("1.e+", 2, 4), ("1e+", 1, 3), ("0x1.p", 4, 5), ("0x1.8", 2, 5), ("1_", 1, 2), ("1__0", 1, 3), ("1.5__", 3, 5), ("0x1_", 3, 4)] {
+        match NewScanner(src).ScanToken() {
+            Err(BasicScannerError::MalformedNumber(err)) => {
+                assert_eq!((err.PosRange.Begin.Offset, err.PosRange.End.Offset), (begin, end), "{}", src);
+            }
+            _ => { panic!("{}", src) }
+        }
+    }
+}
+
+#[test]
+fn TestScanner_Char() {
+    for (src, ch) in [("'a'", 'a'), ("'\\n'", '\n'), ("'\\''", '\''), ("'\\u554a'", '啊'), ("'啊'", '啊')] {
+        let tok = NewScanner(src).Scan().unwrap();
+        assert!(matches!(tok.Kind, BasicTokenKind::Char), "{}", src);
+        assert_eq!(tok.Literal, ch.to_string());
+    }
+
+    let tok = NewScanner("\"a\"").Scan().unwrap();
+    assert!(matches!(tok.Kind, BasicTokenKind::String));
+
+    for (src, end) in [("''", 2), ("'ab'", 4)] {
+        match NewScanner(src).ScanToken() {
+            Err(BasicScannerError::BadFormat(err)) => {
+                assert_eq!((err.PosRange.Begin.Offset, err.PosRange.End.Offset), (0, end), "{}", src);
+            }
+            _ => { panic!("{}", src) }
+        }
+    }
+
+    // the newline is not part of the literal
+    match NewScanner("'a\n'").ScanToken() {
+        Err(BasicScannerError::UnterminatedString(err)) => { assert_eq!((err.PosRange.Begin.Offset, err.PosRange.End.Offset), (0, 2)); }
+        _ => { panic!() }
+    }
+}
+
+#[test]
+fn TestScanner_RawAndBlockString() {
+    let src = "r#\"SELECT \"name\"\n\\n\"# r\"a\"";
+    let mut s = NewScanner(src);
+    let tok = s.Scan().unwrap();
+    assert!(matches!(tok.Kind, BasicTokenKind::String));
+    assert_eq!(tok.Literal, "SELECT \"name\"\n\\n");
+    assert_eq!(s.Slice(tok.Pos), "r#\"SELECT \"name\"\n\\n\"#");
+    assert_eq!(tok.Pos.End.Line, 1);
+    let tok = s.Scan().unwrap();
+    assert_eq!(tok.Literal, "a");
+
+    let src = "\"\"\"\n    {\n      \"k\": \"\\u554a\"\n\n    }\n    \"\"\"";
+    let mut s = NewScanner(src);
+    let tok = s.Scan().unwrap();
+    assert_eq!(tok.Literal, "{\n  \"k\": \"啊\"\n\n}");
+    assert_eq!(s.Slice(tok.Pos).len(), src.len());
+
+    let src = "\"\"\"\n    a\n  b\n    \"\"\"";
+    match NewScanner(src).ScanToken() {
+        Err(BasicScannerError::BadFormat(err)) => {
+            assert_eq!((err.PosRange.Begin.Line, err.PosRange.Begin.Column, err.PosRange.End.Column), (2, 0, 2));
+        }
+        _ => { panic!() }
+    }
+}
+
+#[test]
+fn TestScanner_Interpolation() {
+    let mut s = NewScanner(r#""hello {name}, {f({1})}!\{" "#);
+    let mut literals = vec![];
+    loop {
+        let tok = s.Scan().unwrap();
+        literals.push(tok.Literal.to_string());
+        if matches!(tok.Kind, BasicTokenKind::StringTail) { break; }
+    }
+    assert_eq!(literals, vec!["hello ", "name", ", ", "f", "(", "{", "1", "}", ")", "!{"]);
+    assert!(s.Interpolations.is_empty());
+}
+
+#[test]
+fn TestScanner_EmptyInterpolation() {
+    let mut s = NewScanner(r#""a{ }b" x"#);
+    assert!(matches!(s.Scan().unwrap().Kind, BasicTokenKind::Invalid));
+    assert!(matches!(s.Scan().unwrap().Kind, BasicTokenKind::Ident));
+    match &s.Errors[..] {
+        [BasicScannerError::EmptyInterpolation(err)] => { assert_eq!((err.PosRange.Begin.Column, err.PosRange.End.Column), (2, 5)); }
+        _ => { panic!() }
+    }
+}
+
+#[test]
+fn TestScanner_UnicodeIdent() {
+    let mut s = NewScanner("café _private x1 nai\u{308}ve pаypal paypal");
+    let mut idents = vec![];
+    while let Ok(tok) = s.Scan() {
+        assert!(matches!(tok.Kind, BasicTokenKind::Ident));
+        idents.push(tok.Literal.to_string());
+    }
+    assert_eq!(idents, vec!["café", "_private", "x1", "naïve", "pаypal", "paypal"]);
+    assert_eq!(idents[3].chars().count(), 5); // NFC: 'i' + U+0308 composes to 'ï'
+
+    assert!(matches!(&s.Warnings[..], [BasicScannerWarning::MixedScriptIdent(_), BasicScannerWarning::ConfusableIdent(w)] if w.Similar == "pаypal"));
+}
+
+#[test]
+fn TestScanner_IntLiteral() {
+    for (src, value, suffix) in [
+        ("0", 0, None),
+        ("0u8", 0, Some(IntSuffix::U8)),
+        ("1_000_000", 1_000_000, None),
+        ("0xFF_ffu16", 0xffff, Some(IntSuffix::U16)),
+        ("0o777", 0o777, None),
+        ("0b1010i8", 10, Some(IntSuffix::I8)),
+        ("127i8", 127, Some(IntSuffix::I8)),
+        ("128i8", 128, Some(IntSuffix::I8)), // the magnitude of i8's minimum, for `-128i8`
+        ("340282366920938463463374607431768211455", u128::MAX, None),
+    ] {
+        let tok = NewScanner(src).Scan().unwrap();
+        let format = match tok.Kind {
+            BasicTokenKind::Int(format) => { format }
+            _ => { panic!("{}", src) }
+        };
+        let literal = DecodeInt(format, &tok.Literal).unwrap();
+        assert_eq!((literal.Value, literal.Suffix), (value, suffix), "{}", src);
+    }
+
+    for src in ["256u8", "129i8", "300i8", "0x1_0000_0000u32", "0x81i8", "340282366920938463463374607431768211456"] {
+        match NewScanner(src).ScanToken() {
+            Err(BasicScannerError::OutOfRange(err)) => {
+                assert_eq!((err.PosRange.Begin.Offset, err.PosRange.End.Offset), (0, src.len()), "{}", src);
+            }
+            _ => { panic!("{}", src) }
+        }
+    }
+
+    for (src, begin) in [("1u7", 1), ("0o78", 3), ("1.5u8", 3)] {
+        match NewScanner(src).ScanToken() {
+            Err(BasicScannerError::MalformedNumber(err)) => { assert_eq!(err.PosRange.Begin.Offset, begin, "{}", src); }
+            _ => { panic!("{}", src) }
+        }
+    }
+}
+
+#[test]
+fn TestScanner_Comment() {
+    let mut s = NewScanner("/* a /* b */ c */ /** doc */ /**/ /// line doc\n//// plain\n// x");
+    let mut comments = vec![];
+    while let Ok(tok) = s.Scan() {
+        comments.push((matches!(tok.Kind, BasicTokenKind::DocComment), tok.Literal.to_string()));
+        if s.GetChar().ok() == Some('\n') {
+            s.Move().unwrap();
+        }
+    }
+    assert_eq!(comments, vec![
+        (false, " a /* b */ c ".to_string()),
+        (true, " doc ".to_string()),
+        (false, "".to_string()),
+        (true, " line doc".to_string()),
+        (false, "// plain".to_string()),
+        (false, " x".to_string()),
+    ]);
+
+    let mut s = NewScanner("a+//b");
+    assert_eq!(s.Scan().unwrap().Literal, "a");
+    assert_eq!(s.Scan().unwrap().Literal, "+");
+    assert!(matches!(s.Scan().unwrap().Kind, BasicTokenKind::Comment));
+}
+
+#[test]
+fn TestScanner_Operator() {
+    let mut s = NewScanner("a+-b x!=-1 &&!ok a<<=1 a<<<b <-@");
+    let mut literals = vec![];
+    while let Ok(tok) = s.Scan() {
+        if matches!(tok.Kind, BasicTokenKind::Operator) {
+            literals.push(tok.Literal.to_string());
+        }
+    }
+    assert_eq!(literals, vec!["+", "-", "!=", "-", "&&", "!", "<<=", "<<", "<", "<-"]);
+    match &s.Errors[..] {
+        [BasicScannerError::InvalidChar(err)] => { assert_eq!(err.PosRange.Begin.Offset, 31); }
+        _ => { panic!() }
+    }
+
+    let mut s = NewScanner("$ # a");
+    assert!(matches!(s.Scan().unwrap().Kind, BasicTokenKind::Invalid));
+    assert!(matches!(s.Scan().unwrap().Kind, BasicTokenKind::Invalid));
+    assert!(matches!(s.Scan().unwrap().Kind, BasicTokenKind::Ident));
+    assert_eq!(s.Errors.len(), 2);
+}
+
+#[test]
+fn TestScanner_Recover() {
+    let src = "a 1.e+ b § c \"x\\qy\" d '\\u12' e /* open";
+    let mut s = NewScanner(src);
+    let mut tokens = vec![];
+    while let Ok(tok) = s.Scan() {
+        tokens.push((matches!(tok.Kind, BasicTokenKind::Invalid), tok.Literal.to_string()));
+    }
+    assert_eq!(tokens, vec![
+        (false, "a".to_string()), (true, "1.e+".to_string()),
+        (false, "b".to_string()), (true, "§".to_string()),
+        (false, "c".to_string()), (true, "\"x\\qy\"".to_string()),
+        (false, "d".to_string()), (true, "'\\u12'".to_string()),
+        (false, "e".to_string()), (true, "/* open".to_string()),
+    ]);
+
+    let kinds = s.Errors.iter().map(|e| match e {
+        BasicScannerError::MalformedNumber(_) => { "number" }
+        BasicScannerError::InvalidChar(_) => { "char" }
+        BasicScannerError::BadEscape(_) => { "escape" }
+        BasicScannerError::UnterminatedComment(_) => { "comment" }
+        _ => { "other" }
+    }).collect::<Vec<_>>();
+    assert_eq!(kinds, vec!["number", "char", "escape", "escape", "comment"]);
+
+    let mut s = NewScanner("x \"abc");
+    s.Scan().unwrap();
+    let tok = s.Scan().unwrap();
+    assert!(matches!(tok.Kind, BasicTokenKind::Invalid));
+    assert!(matches!(s.Errors[..], [BasicScannerError::UnterminatedString(_)]));
+    assert!(matches!(s.Scan(), Err(BasicScannerError::EOF(_))));
+
+    // an unterminated string stops at the end of its line, the next line scans normally
+    let mut s = BasicScanner::new("a § \"bc\nd § e 'f\ng", vec!['\n'], vec![' '], vec![]);
+    let mut tokens = vec![];
+    while let Ok(tok) = s.Scan() {
+        tokens.push((matches!(tok.Kind, BasicTokenKind::Invalid), tok.Literal.to_string()));
+    }
+    assert_eq!(tokens, vec![
+        (false, "a".to_string()), (true, "§".to_string()), (true, "\"bc".to_string()),
+        (false, "\n".to_string()), (false, "d".to_string()), (true, "§".to_string()), (false, "e".to_string()),
+        (true, "'f".to_string()), (false, "\n".to_string()), (false, "g".to_string()),
+    ]);
+    match &s.Errors[..] {
+        [BasicScannerError::InvalidChar(_), BasicScannerError::UnterminatedString(err), BasicScannerError::InvalidChar(_), BasicScannerError::UnterminatedString(char)] => {
+            assert_eq!((err.PosRange.Begin.Line, err.PosRange.Begin.Column), (0, 4));
+            assert_eq!((char.PosRange.Begin.Line, char.PosRange.Begin.Column, char.PosRange.End.Column), (1, 6, 8));
+        }
+        _ => { panic!() }
+    }
+}
+
+#[test]
+fn TestScanner_Iterator() {
+    let literals = NewScanner("a + 1 § b").map(|tok| tok.Literal).collect::<Vec<_>>();
+    assert_eq!(literals, vec!["a", "+", "1", "§", "b"]);
+}
+
+#[test]
+fn TestSourceMap() {
+    let mut sm = SourceMap::default();
+    let a = sm.Add(String::from("a.cee"), String::from("\"é\" + 啊"));
+    let pos = sm.Get(a).Locate(7); // byte offset of '啊'
+    assert_eq!((pos.Line, pos.Column), (0, 6));
+    let id = sm.Add(String::from("b.cee"), String::from("let a = 1\n\n  let b = 1.e+\n"));
+
+    let file = sm.Get(id);
+    assert_eq!(file.LineStarts, vec![0, 10, 11, 26]);
+    let pos = file.Locate(15);
+    assert_eq!((pos.Line, pos.Column), (2, 4));
+
+    let mut s = NewScanner("");
+    s.BufferScanner = BufferScanner::new(&file.Buffer);
+    s.BufferScanner.File = id;
+    s.Whitespaces.push('\n');
+    let err = loop {
+        match s.ScanToken() {
+            Ok(tok) => { assert_eq!(tok.Pos.File, id); }
+            Err(err) => { break err; }
+        }
+    };
+    assert_eq!(err.Render(&sm), "b.cee:3:13: malformed number");
+}
+
+fn NewIncrementalScanner(src: &str) -> IncrementalScanner {
+    IncrementalScanner::new(String::from(src), vec!['(', ')', '[', ']', '{', '}', ',', ';', '\n'], vec![' ', '\t'], vec!["..", "=>", "<-", "+", "-", ".", "=", "<", "*", "/"])
+}
+
+fn AssertSameTokens(have: &IncrementalScanner, want: &IncrementalScanner) {
+    assert_eq!(have.Tokens.len(), want.Tokens.len(), "{:?}", have.Buffer);
+    for (a, b) in have.Tokens.iter().zip(want.Tokens.iter()) {
+        assert_eq!((&a.Token.Pos, &a.Token.Kind, &a.Token.Literal), (&b.Token.Pos, &b.Token.Kind, &b.Token.Literal), "{:?}", want.Buffer);
+        assert_eq!(a.Interpolations, b.Interpolations);
+    }
+    let errors = |s: &IncrementalScanner| s.Errors().map(|e| format!("{:?}", e)).collect::<Vec<_>>();
+    assert_eq!(errors(have), errors(want), "{:?}", want.Buffer);
+}
+
+#[test]
+fn TestIncrementalScanner_Apply() {
+    let mut s = NewIncrementalScanner("let a = 1\nlet b = \"x {a} y\"\nlet c = b\n");
+    let n = s.Tokens.len();
+
+    // `a` -> `a2`: only the tokens around the edit are scanned again
+    let changed = s.Apply(&TextEdit { Begin: 4, End: 5, Text: String::from("a2") });
+    assert!(changed.len() <= 3, "{:?}", changed);
+    assert_eq!(s.Tokens.len(), n);
+    let last = &s.Tokens[n - 2].Token;
+    assert_eq!((last.Literal.as_ref(), last.Pos.Begin.Offset, last.Pos.Begin.Line, last.Pos.Begin.Column), ("b", 37, 2, 8));
+
+    // an opening quote changes every token up to the end of file
+    s.Apply(&TextEdit { Begin: 0, End: 0, Text: String::from("\"") });
+    AssertSameTokens(&s, &NewIncrementalScanner(&s.Buffer));
+
+    // errors after the edit move with their tokens, errors of rescanned tokens are replaced
+    let mut s = NewIncrementalScanner("a § b
+c § d
+");
+    s.Apply(&TextEdit { Begin: 0, End: 0, Text: String::from("x\n") });
+    AssertSameTokens(&s, &NewIncrementalScanner(&s.Buffer));
+    assert_eq!(s.Errors().count(), 2);
+    s.Apply(&TextEdit { Begin: 4, End: 6, Text: String::new() });
+    AssertSameTokens(&s, &NewIncrementalScanner(&s.Buffer));
+    assert_eq!(s.Errors().count(), 1);
+}
+
+const Fragments: &[&str] = &[
+    "a", "bc", "é", "_1", " ", "\n", "0", "1", ".", "e", "0x", "\"", "\"\"\"", "'", "\\", "{", "}",
+    "(", ")", "/", "*", "+", "=", "<", "-", "r", "#", "§",
+];
+
+fn Fragment() -> impl Strategy<Value=String> {
+    prop::collection::vec(prop::sample::select(Fragments), 0..4).prop_map(|v| v.concat())
+}
+
+proptest! {
+    #[test]
+    fn TestIncrementalScanner_MatchesFullScan(
+        src in prop::collection::vec(prop::sample::select(Fragments), 0..40).prop_map(|v| v.concat()),
+        edits in prop::collection::vec((any::<prop::sample::Index>(), any::<prop::sample::Index>(), Fragment()), 1..6),
+    ) {
+        let mut s = NewIncrementalScanner(&src);
+        for (a, b, text) in edits {
+            let bounds = s.Buffer.char_indices().map(|(i, _)| i).chain([s.Buffer.len()]).collect::<Vec<_>>();
+            let (a, b) = (*a.get(&bounds), *b.get(&bounds));
+            s.Apply(&TextEdit { Begin: a.min(b), End: a.max(b), Text: text });
+            AssertSameTokens(&s, &NewIncrementalScanner(&s.Buffer));
+        }
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright 2024 LangVM Project
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use crate::scanner::*;
+
+pub struct SourceFile {
+    pub Id: FileId,
+    pub Name: String,
+    pub Buffer: String,
+
+    // Byte offset of the first char of every line
+    pub LineStarts: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(id: FileId, name: String, buffer: String) -> SourceFile {
+        let mut lineStarts = vec![0];
+        for (offset, ch) in buffer.bytes().enumerate() {
+            if ch == b'\n' {
+                lineStarts.push(offset + 1);
+            }
+        }
+
+        SourceFile { Id: id, Name: name, Buffer: buffer, LineStarts: lineStarts }
+    }
+
+    // Line and column of a byte offset, both counting from 0; the column counts chars.
+    pub fn Locate(&self, offset: usize) -> Position {
+        let line = self.LineStarts.partition_point(|it| *it <= offset) - 1;
+        Position { Offset: offset, Line: line, Column: self.Buffer[self.LineStarts[line]..offset].chars().count() }
+    }
+}
+
+// Owns every loaded file; FileId indexes Files.
+#[derive(Default)]
+pub struct SourceMap {
+    pub Files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn Add(&mut self, name: String, buffer: String) -> FileId {
+        let id = FileId(self.Files.len() as u32);
+        self.Files.push(SourceFile::new(id, name, buffer));
+        id
+    }
+
+    pub fn Get(&self, id: FileId) -> &SourceFile { &self.Files[id.0 as usize] }
+
+    // Renders the beginning of the range as `file.cee:12:5`.
+    pub fn Render(&self, pos: PosRange) -> String {
+        let file = self.Get(pos.File);
+        format!("{}:{}", file.Name, file.Locate(pos.Begin.Offset))
+    }
+}
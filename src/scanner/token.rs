@@ -2,26 +2,33 @@
 // This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
+use std::borrow::Cow;
 use std::fmt::Display;
 
 use crate::scanner::*;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum BasicTokenKind {
     Ident,
     Operator,
 
     Int(IntFormat),
-    Float,
+    Float(IntFormat),
     String,
+    StringHead,
+    StringMiddle,
+    StringTail,
     Char,
 
     Delimiter,
 
     Comment,
+    DocComment,
+    // Source that failed to scan, see BasicScanner::Errors
+    Invalid,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum IntFormat {
     BIN = 2,
     OCT = 8,
@@ -29,8 +36,9 @@ pub enum IntFormat {
     HEX = 16,
 }
 
-pub struct BasicToken {
+// Literal borrows from the source unless scanning changed it, e.g. by escape processing.
+pub struct BasicToken<'a> {
     pub Pos: PosRange,
     pub Kind: BasicTokenKind,
-    pub Literal: Vec<char>,
+    pub Literal: Cow<'a, str>,
 }